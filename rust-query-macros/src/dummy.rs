@@ -1,42 +1,104 @@
 use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote};
-use syn::{GenericParam, ItemStruct, Lifetime};
+use syn::{GenericParam, ItemStruct, WherePredicate};
 
 use crate::make_generic;
 
 struct CommonInfo {
     name: syn::Ident,
     dummy_name: syn::Ident,
-    original_generics: Vec<Lifetime>,
-    fields: Vec<(syn::Ident, syn::Type)>,
+    /// Generic parameters with their bounds, used to declare the generics of impls.
+    original_params: Vec<GenericParam>,
+    /// Generic arguments, used to name the struct type.
+    original_generics: Vec<TokenStream>,
+    where_clause: Vec<WherePredicate>,
+    fields: Vec<Field>,
+}
+
+struct Field {
+    name: syn::Ident,
+    typ: syn::Type,
+    flatten: bool,
+    rename: Option<syn::Ident>,
+}
+
+impl Field {
+    fn from_field(field: syn::Field) -> syn::Result<Self> {
+        let Some(name) = field.ident else {
+            return Err(syn::Error::new_spanned(
+                field,
+                "Tuple structs are not supported (yet).",
+            ));
+        };
+        let mut flatten = false;
+        let mut rename = None;
+        for attr in &field.attrs {
+            if attr.path().is_ident("rust_query") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("flatten") {
+                        flatten = true;
+                        return Ok(());
+                    }
+                    if meta.path.is_ident("rename") {
+                        let column: syn::LitStr = meta.value()?.parse()?;
+                        rename = Some(column.parse()?);
+                        return Ok(());
+                    }
+                    Err(meta.error("unrecognized rust-query field attribute"))
+                })?;
+            }
+        }
+        if flatten && rename.is_some() {
+            return Err(syn::Error::new_spanned(
+                name,
+                "Flattened fields can not be renamed.",
+            ));
+        }
+        Ok(Self {
+            name,
+            typ: field.ty,
+            flatten,
+            rename,
+        })
+    }
 }
 
 impl CommonInfo {
     fn from_item(item: ItemStruct) -> syn::Result<Self> {
         let name = item.ident;
         let dummy_name = format_ident!("{name}Dummy");
-        let original_generics = item.generics.params.into_iter().map(|x| {
-            let GenericParam::Lifetime(lt) = x else {
-                return Err(syn::Error::new_spanned(
-                    x,
-                    "Only lifetime generics are supported.",
-                ));
-            };
-            Ok(lt.lifetime)
+        let original_generics = item.generics.params.iter().map(|x| match x {
+            GenericParam::Lifetime(lt) => {
+                let lt = &lt.lifetime;
+                quote! {#lt}
+            }
+            GenericParam::Type(typ) => {
+                let ident = &typ.ident;
+                quote! {#ident}
+            }
+            GenericParam::Const(cst) => {
+                let ident = &cst.ident;
+                quote! {#ident}
+            }
         });
-        let fields = item.fields.into_iter().map(|field| {
-            let Some(name) = field.ident else {
-                return Err(syn::Error::new_spanned(
-                    field,
-                    "Tuple structs are not supported (yet).",
-                ));
-            };
-            Ok((name, field.ty))
+        let original_generics = original_generics.collect();
+        let original_params = item.generics.params.into_iter().map(|mut x| {
+            // defaults are not allowed on impl generics
+            match &mut x {
+                GenericParam::Type(typ) => typ.default = None,
+                GenericParam::Const(cst) => cst.default = None,
+                GenericParam::Lifetime(_) => {}
+            }
+            x
         });
+        let where_clause = item.generics.where_clause.into_iter();
+        let fields = item.fields.into_iter().map(Field::from_field);
         Ok(Self {
             name,
             dummy_name,
-            original_generics: original_generics.collect::<Result<_, _>>()?,
+            original_params: original_params.collect(),
+            original_generics,
+            where_clause: where_clause.flat_map(|x| x.predicates).collect(),
             fields: fields.collect::<Result<_, _>>()?,
         })
     }
@@ -83,15 +145,19 @@ pub fn from_row_impl(item: ItemStruct) -> syn::Result<TokenStream> {
     let CommonInfo {
         name,
         dummy_name,
+        original_params,
         original_generics,
+        where_clause,
         fields,
     } = CommonInfo::from_item(item)?;
 
-    let mut original_plus_transaction = original_generics.clone();
+    // lifetimes have to come before the other generics, so the transaction lifetime is first
+    let mut original_plus_transaction = vec![];
     let builtin_lt = syn::Lifetime::new("'_a", Span::mixed_site());
     if transaction_lt.is_none() {
-        original_plus_transaction.push(builtin_lt.clone());
+        original_plus_transaction.push(quote! {#builtin_lt});
     }
+    original_plus_transaction.extend(original_params.iter().map(|x| quote! {#x}));
     let transaction_lt = transaction_lt.unwrap_or(builtin_lt);
 
     let mut defs = vec![];
@@ -103,7 +169,7 @@ pub fn from_row_impl(item: ItemStruct) -> syn::Result<TokenStream> {
     let mut names = vec![];
     let mut from_impl = vec![];
     let mut from_conds = vec![];
-    for (name, typ) in &fields {
+    for Field { name, typ, .. } in &fields {
         let generic = make_generic(name);
 
         defs.push(quote! {#name: #generic});
@@ -121,12 +187,27 @@ pub fn from_row_impl(item: ItemStruct) -> syn::Result<TokenStream> {
     let trivial = trivial.into_iter().map(|trivial| {
         let schema = quote! {<#trivial as ::rust_query::Table>::Schema};
         let mut trivial_prepared = vec![];
-        for (name, typ) in &fields {
-            trivial_prepared
-                .push(quote! {#name: <#typ as ::rust_query::dummy::FromColumn<_, _>>::from_column(col.#name())});
+        for Field {
+            name,
+            typ,
+            flatten,
+            rename,
+        } in &fields
+        {
+            // flattened fields use the same column as the struct itself
+            let col = if *flatten {
+                quote! {col.clone()}
+            } else {
+                let column = rename.as_ref().unwrap_or(name);
+                quote! {col.#column()}
+            };
+            trivial_prepared.push(
+                quote! {#name: <#typ as ::rust_query::dummy::FromColumn<_, _>>::from_column(#col)},
+            );
         }
         quote! {
             impl<#(#original_plus_transaction),*> ::rust_query::dummy::FromColumn<#transaction_lt, #schema, #trivial> for #name<#(#original_generics),*>
+            where #(#where_clause,)*
             {
                 fn from_column<'_t>(col: ::rust_query::Column<'_t, #schema, #trivial>) -> ::rust_query::Dummy<'_t, #transaction_lt, #schema, Self::Impl> {
                     ::rust_query::IntoDummy::into_dummy(#dummy_name {
@@ -148,7 +229,9 @@ pub fn from_row_impl(item: ItemStruct) -> syn::Result<TokenStream> {
             #(#defs),*
         }
 
-        impl<'_t #(,#original_plus_transaction)*, S #(,#constraints)*> ::rust_query::IntoDummy<'_t, #transaction_lt, S> for #dummy_name<#(#generics),*> {
+        impl<'_t #(,#original_plus_transaction)*, S #(,#constraints)*> ::rust_query::IntoDummy<'_t, #transaction_lt, S> for #dummy_name<#(#generics),*>
+        where #(#where_clause,)*
+        {
             type Out = #name<#(#original_generics),*>;
             type Impl = ::rust_query::dummy::MapImpl<#parts_into_impl, fn(#parts_typ) -> Self::Out>;
 
@@ -159,8 +242,8 @@ pub fn from_row_impl(item: ItemStruct) -> syn::Result<TokenStream> {
             }
         }
 
-        impl<#(#original_generics),*> ::rust_query::dummy::FromDummy for #name<#(#original_generics),*>
-        where #(#from_conds,)*
+        impl<#(#original_params),*> ::rust_query::dummy::FromDummy for #name<#(#original_generics),*>
+        where #(#from_conds,)* #(#where_clause,)*
         {
             type Impl = ::rust_query::dummy::MapImpl<#parts_from_impl, fn(#parts_typ) -> Self>;
         }
@@ -3,7 +3,7 @@ use std::{collections::BTreeMap, ops::Not};
 use dummy::from_row_impl;
 use heck::{ToSnekCase, ToUpperCamelCase};
 use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, ToTokens};
 use syn::{
     punctuated::Punctuated, Attribute, Ident, ItemEnum, ItemStruct, Meta, Path, Token, Type,
 };
@@ -17,11 +17,14 @@ mod table;
 /// - `i64` (sqlite `integer`)
 /// - `f64` (sqlite `real`)
 /// - `String` (sqlite `text`)
+/// - `Vec<u8>` (sqlite `blob`)
+/// - `bool` (sqlite `integer` with a `CHECK (col IN (0, 1))` constraint)
 /// - Any table in the same schema (sqlite `integer` with foreign key constraint)
+/// - `rust_query::Json<T>` (sqlite `text`, requires the `json` feature)
+/// - `chrono::NaiveDateTime` and `chrono::DateTime<chrono::Utc>` (sqlite `text`, requires the `chrono` feature)
+/// - `uuid::Uuid` (sqlite `blob`, requires the `uuid` feature)
 /// - `Option<T>` where `T` is not an `Option` (sqlite nullable)
 ///
-/// Booleans are not supported in schemas yet.
-///
 /// ## Unique constraints
 ///
 /// For example:
@@ -47,6 +50,69 @@ mod table;
 ///
 /// Optional types are not allowed in unique constraints.
 ///
+/// ## Default values
+///
+/// Columns can have a default value that is used for new rows.
+/// ```
+/// #[rust_query::migration::schema]
+/// enum Schema {
+///     Post {
+///         title: String,
+///         #[default(0)]
+///         views: i64,
+///         #[default(rust_query::UnixEpoch)]
+///         created: i64,
+///     }
+/// }
+/// # fn main() {}
+/// ```
+/// The default value is an expression that is stored in the schema as the sqlite column `DEFAULT`.
+/// Note that the expression is evaluated inside the generated module, so it is best to use absolute paths.
+///
+/// Columns with a default value can be set to `()` when inserting, in which case the default value is used.
+/// When updating, setting the column to `()` keeps the current value.
+///
+/// Columns with a default value are not allowed in unique constraints.
+///
+/// ## Generated columns
+///
+/// Columns can be computed from other columns in the same row.
+/// ```
+/// #[rust_query::migration::schema]
+/// enum Schema {
+///     Customer {
+///         name: String,
+///         #[generated("lower(name)", stored)]
+///         search_name: String,
+///         #[generated("length(name)")]
+///         name_len: i64,
+///     }
+/// }
+/// # fn main() {}
+/// ```
+/// The expression is sqlite and is stored in the schema as `GENERATED ALWAYS AS (expr)`.
+/// Generated columns are `VIRTUAL` by default, which means they are computed when read.
+/// Add `stored` to compute the value when the row is written instead.
+///
+/// Generated columns can be read like any other column, but they can not be set when inserting or updating.
+/// They can not have a default value and are not allowed in unique constraints.
+///
+/// ## Blob length
+///
+/// Blob columns can be restricted to an exact number of bytes.
+/// ```
+/// #[rust_query::migration::schema]
+/// enum Schema {
+///     ApiKey {
+///         #[blob_len(32)]
+///         hash: Vec<u8>,
+///     }
+/// }
+/// # fn main() {}
+/// ```
+/// This adds a `CHECK (length(col) = 32)` constraint to the column.
+/// Inserting a blob with a different length will then fail the check.
+///
 /// ## Multiple versions
 /// The macro uses enum syntax, but it generates multiple modules of types.
 ///
@@ -134,6 +200,10 @@ mod table;
 /// ```rust,ignore
 /// #[version(..3)]
 /// ```
+/// If columns are only deleted from a table, then they are removed with `ALTER TABLE DROP COLUMN`
+/// and no migration needs to be specified for the table.
+/// This is not possible for columns that are part of a unique constraint or that reference another table,
+/// or if the table has generated columns. In that case the table is migrated like any other table.
 /// You can make a multi column unique constraint by specifying it before the table.
 /// ```rust,ignore
 /// #[unique(user, game)]
@@ -212,6 +282,10 @@ pub fn schema(
 /// }
 /// ```
 ///
+/// The struct can have generic parameters and a where clause.
+/// They are also added to the generated implementations, so a generic field `T` can be
+/// filled with any dummy that has `T` as output.
+///
 /// This macro also supports some helper attributes on the struct.
 ///
 /// - `#[rust_query(From = Thing)]`
@@ -219,6 +293,16 @@ pub fn schema(
 /// - `#[rust_query(lt = 't)]`
 ///   Can be used to specify the transaction lifetime for structs that contain `TableRow` fields.
 ///   This is only necessary when using `#[rust_query(From = Thing)]`.
+///
+/// The following helper attributes are supported on fields.
+///
+/// - `#[rust_query(flatten)]`
+///   When deriving `FromColumn`, this will use the `FromColumn` implementation of the field type
+///   for the same column, instead of for the column with the same name as the field.
+///   This makes it possible to share a struct with some common fields between multiple structs.
+/// - `#[rust_query(rename = "column")]`
+///   When deriving `FromColumn`, this will use the column with the given name instead of
+///   the column with the same name as the field.
 #[proc_macro_derive(Dummy, attributes(rust_query))]
 pub fn from_row(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let item = syn::parse_macro_input!(item as ItemStruct);
@@ -248,6 +332,34 @@ struct Unique {
 struct Column {
     name: Ident,
     typ: Type,
+    default: Option<syn::Expr>,
+    generated: Option<Generated>,
+    blob_len: Option<syn::LitInt>,
+}
+
+#[derive(Clone)]
+struct Generated {
+    expr: syn::LitStr,
+    stored: bool,
+}
+
+impl syn::parse::Parse for Generated {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let expr = input.parse()?;
+        let mut stored = false;
+        if input.parse::<Option<Token![,]>>()?.is_some() {
+            let kind: Ident = input.parse()?;
+            if kind == "stored" {
+                stored = true;
+            } else if kind != "virtual" {
+                return Err(syn::Error::new_spanned(
+                    kind,
+                    "Expected `stored` or `virtual`.",
+                ));
+            }
+        }
+        Ok(Generated { expr, stored })
+    }
 }
 
 #[derive(Clone)]
@@ -356,6 +468,10 @@ fn define_table_migration(
     let prev_columns_uwrapped = prev_columns.unwrap_or(const { &BTreeMap::new() });
 
     for (i, col) in &table.columns {
+        if col.generated.is_some() {
+            // generated columns are computed by sqlite
+            continue;
+        }
         let name = &col.name;
         let name_str = col.name.to_string();
         let typ = &col.typ;
@@ -426,6 +542,41 @@ fn define_table_migration(
     Some(migration)
 }
 
+/// Returns the names of the removed columns if they can be dropped with `ALTER TABLE DROP COLUMN`.
+///
+/// Sqlite does not allow dropping columns that are used in a unique constraint or foreign key.
+/// Columns could also be used in the expression of a generated column.
+fn dropped_columns(prev: &Table, table: &Table, table_names: &[String]) -> Option<Vec<String>> {
+    if table.columns.keys().any(|i| !prev.columns.contains_key(i)) {
+        return None;
+    }
+    if prev.columns.values().any(|col| col.generated.is_some()) {
+        return None;
+    }
+    let mut dropped = vec![];
+    for (i, col) in &prev.columns {
+        if table.columns.contains_key(i) {
+            continue;
+        }
+        if prev.uniques.iter().any(|u| u.columns.contains(&col.name)) {
+            return None;
+        }
+        if mentions_ident(col.typ.to_token_stream(), table_names) {
+            return None;
+        }
+        dropped.push(col.name.to_string());
+    }
+    Some(dropped)
+}
+
+fn mentions_ident(tokens: TokenStream, names: &[String]) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(ident) => names.contains(&ident.to_string()),
+        proc_macro2::TokenTree::Group(group) => mentions_ident(group.stream(), names),
+        _ => false,
+    })
+}
+
 fn is_unique(path: &Path) -> Option<Ident> {
     path.get_ident().and_then(|ident| {
         ident
@@ -498,8 +649,35 @@ fn generate(item: ItemEnum) -> syn::Result<TokenStream> {
                 }
                 let mut other_attrs = vec![];
                 let mut unique = None;
+                let mut default = None;
+                let mut generated = None;
+                let mut blob_len = None;
                 for attr in &field.attrs {
-                    if let Some(unique_name) = is_unique(attr.path()) {
+                    if attr.path().is_ident("default") {
+                        if default.is_some() {
+                            return Err(syn::Error::new_spanned(
+                                attr,
+                                "There should be only one default attribute.",
+                            ));
+                        }
+                        default = Some(attr.parse_args()?);
+                    } else if attr.path().is_ident("generated") {
+                        if generated.is_some() {
+                            return Err(syn::Error::new_spanned(
+                                attr,
+                                "There should be only one generated attribute.",
+                            ));
+                        }
+                        generated = Some(attr.parse_args()?);
+                    } else if attr.path().is_ident("blob_len") {
+                        if blob_len.is_some() {
+                            return Err(syn::Error::new_spanned(
+                                attr,
+                                "There should be only one blob_len attribute.",
+                            ));
+                        }
+                        blob_len = Some(attr.parse_args()?);
+                    } else if let Some(unique_name) = is_unique(attr.path()) {
                         let Meta::Path(_) = &attr.meta else {
                             return Err(syn::Error::new_spanned(
                                 attr,
@@ -518,9 +696,18 @@ fn generate(item: ItemEnum) -> syn::Result<TokenStream> {
                 if !range.includes(version) {
                     continue;
                 }
+                if default.is_some() && generated.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "Generated columns can not have a default value.",
+                    ));
+                }
                 let col = Column {
                     name,
                     typ: field.ty.clone(),
+                    default,
+                    generated,
+                    blob_len,
                 };
                 columns.insert(i, col);
                 uniques.extend(unique);
@@ -545,6 +732,8 @@ fn generate(item: ItemEnum) -> syn::Result<TokenStream> {
 
         let mut table_migrations = TokenStream::new();
 
+        let prev_table_names: Vec<_> = prev_tables.values().map(|t| t.name.to_string()).collect();
+
         // loop over all new table and see what changed
         for (i, table) in &new_tables {
             let table_name = &table.name;
@@ -556,6 +745,14 @@ fn generate(item: ItemEnum) -> syn::Result<TokenStream> {
             if let Some(prev_table) = prev_tables.remove(i) {
                 // a table already existed, so we need to define a migration
 
+                if let Some(dropped) = dropped_columns(&prev_table, table, &prev_table_names) {
+                    // columns were only removed, so the table does not need to be rebuilt
+                    for col in dropped {
+                        tables.push(quote! {b.drop_column::<#table_name>(#col)});
+                    }
+                    continue;
+                }
+
                 let Some(migration) = define_table_migration(Some(&prev_table.columns), table)
                 else {
                     continue;
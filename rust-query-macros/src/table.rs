@@ -28,7 +28,7 @@ pub(crate) fn define_table(table: &Table, schema: &Ident) -> syn::Result<TokenSt
         let mut constraints = vec![];
         let mut inits = vec![];
         for col in &unique.columns {
-            let typ = &table
+            let column = table
                 .columns
                 .values()
                 .find(|x| &x.name == col)
@@ -37,8 +37,20 @@ pub(crate) fn define_table(table: &Table, schema: &Ident) -> syn::Result<TokenSt
                         col,
                         "Expected a column to exists for every name in the unique constraint.",
                     )
-                })?
-                .typ;
+                })?;
+            if column.default.is_some() {
+                return Err(syn::Error::new_spanned(
+                    col,
+                    "Columns with a default value can not be part of a unique constraint.",
+                ));
+            }
+            if column.generated.is_some() {
+                return Err(syn::Error::new_spanned(
+                    col,
+                    "Generated columns can not be part of a unique constraint.",
+                ));
+            }
+            let typ = &column.typ;
             let generic = make_generic(col);
 
             args.push(quote! {#col: #generic});
@@ -111,14 +123,34 @@ pub(crate) fn define_table(table: &Table, schema: &Ident) -> syn::Result<TokenSt
                 ::rust_query::private::new_column((::rust_query::private::Col::new(#ident_str, ::rust_query::private::into_owned(&self.0))))
             }
         });
-        reads.push(quote!(f.col(#ident_str, &self.#ident)));
-        def_typs.push(quote!(f.col::<#typ>(#ident_str)));
+        let blob_len = col
+            .blob_len
+            .as_ref()
+            .map(|len| quote!(f.blob_len::<#typ>(#ident_str, #len)));
+        if let Some(generated) = &col.generated {
+            let expr = &generated.expr;
+            let stored = generated.stored;
+            def_typs.push(quote!(f.col_generated::<#typ>(#ident_str, #expr, #stored)));
+            def_typs.extend(blob_len);
+            // generated columns can not be written
+            continue;
+        }
+        if let Some(default) = &col.default {
+            reads.push(quote!(f.col_or_default(#ident_str, &self.#ident)));
+            def_typs.push(quote!(f.col_default::<#typ>(#ident_str, #default)));
+            bounds
+                .push(quote! {#generic: ::rust_query::private::ColumnOrDefault<'t, #schema, #typ>});
+        } else {
+            reads.push(quote!(f.col(#ident_str, &self.#ident)));
+            def_typs.push(quote!(f.col::<#typ>(#ident_str)));
+            bounds.push(quote! {#generic: ::rust_query::IntoColumn<'t, #schema, Typ = #typ>});
+        }
+        def_typs.extend(blob_len);
         let mut unique_columns = table.uniques.iter().flat_map(|x| &x.columns);
         if unique_columns.any(|x| x == ident) {
             def_typs.push(quote!(f.check_unique_compatible::<#typ>()));
         }
         col_defs.push(quote! {pub #ident: #generic});
-        bounds.push(quote! {#generic: ::rust_query::IntoColumn<'t, #schema, Typ = #typ>});
         dummy_columns.push(quote! {::rust_query::Column<'t, #schema, #typ>});
         dummy_inits.push(quote! {#ident: val.#ident()});
         generic_defaults.push(quote! {#generic = ()});
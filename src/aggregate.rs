@@ -5,27 +5,35 @@ use std::{
 };
 
 use ref_cast::RefCast;
-use sea_query::{Expr, Func, SelectStatement, SimpleExpr};
+use sea_query::{
+    Alias, Asterisk, Expr, Frame, FrameType, Func, FunctionCall, Order, OverStatement,
+    SelectStatement, SimpleExpr, WindowStatement,
+};
 
 use crate::{
     alias::{Field, MyAlias},
     ast::MySelect,
     rows::Rows,
     value::{
-        operations::{Const, IsNotNull, UnwrapOr},
-        EqTyp, IntoColumn, MyTyp, NumTyp, Typed, ValueBuilder,
+        operations::{Const, UnwrapOr},
+        EqTyp, IntoColumn, MyTyp, NumTyp, OrdTyp, Typed, ValueBuilder,
     },
     Column, Table,
 };
 
+/// An expression from the outer query that a sub-query is filtered on.
+pub(crate) type OuterExpr = Rc<dyn Fn(ValueBuilder) -> SimpleExpr>;
+
 /// This is the argument type used for aggregates.
 ///
 /// While it is possible to join many tables in an aggregate, there can be only one result.
 /// (The result can be a tuple or struct with multiple values though).
 pub struct Aggregate<'outer, 'inner, S> {
     // pub(crate) outer_ast: &'inner MySelect,
-    pub(crate) conds: Vec<(Field, Rc<dyn Fn(ValueBuilder) -> SimpleExpr>)>,
+    pub(crate) conds: Vec<(Field, OuterExpr)>,
     pub(crate) query: Rows<'inner, S>,
+    // condition that is set by [Aggregate::filtered]
+    pub(crate) filter: Option<SimpleExpr>,
     // pub(crate) table: MyAlias,
     pub(crate) phantom2: PhantomData<fn(&'outer ()) -> &'outer ()>,
 }
@@ -58,6 +66,69 @@ impl<'outer, 'inner, S: 'static> Aggregate<'outer, 'inner, S> {
         }
     }
 
+    /// Apply the condition of [Aggregate::filtered] to an aggregate function call.
+    fn filter_call(&self, call: FunctionCall) -> SimpleExpr {
+        match &self.filter {
+            Some(cond) => Expr::cust_with_exprs("? FILTER (WHERE ?)", [call.into(), cond.clone()]),
+            None => call.into(),
+        }
+    }
+
+    /// Build the source for aggregates that use a sub-query, with the condition of [Aggregate::filtered].
+    fn build_filtered_source(&self) -> SelectStatement {
+        let mut source = self.ast.build_source();
+        if let Some(cond) = &self.filter {
+            source.and_where(cond.clone());
+        }
+        source
+    }
+
+    /// Compute the aggregates in `f` using only the rows where `cond` is true.
+    ///
+    /// Unlike [crate::Rows::filter], this does not remove rows for the other aggregates.
+    /// Note that [crate::Rows::filter] and [Aggregate::filter_on] inside `f` still apply to the whole aggregate.
+    /// This makes it possible to compute aggregates over different subsets of the rows in a single sub-query.
+    /// It uses the sqlite `FILTER (WHERE ...)` clause. Calls to [Aggregate::filtered] can be nested,
+    /// in that case both conditions need to be true.
+    /// ```
+    /// # use rust_query::{aggregate, migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Order { customer: String },
+    /// #     OrderLine { order: Order, delivered: bool },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// # let order = txn.insert(Order { customer: "Alice" });
+    /// # txn.insert(OrderLine { order, delivered: true });
+    /// # txn.insert(OrderLine { order, delivered: false });
+    /// # txn.insert(OrderLine { order, delivered: false });
+    /// let (delivered, total) = txn.query_one(aggregate(|rows| {
+    ///     let line = OrderLine::join(rows);
+    ///     rows.filter_on(line.order(), order);
+    ///     let delivered = rows.filtered(line.delivered(), |rows| rows.count());
+    ///     (delivered, rows.count())
+    /// }));
+    /// assert_eq!((delivered, total), (1, 3));
+    /// ```
+    pub fn filtered<R>(
+        &mut self,
+        cond: impl IntoColumn<'inner, S, Typ = bool>,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let cond = cond.into_column().inner.build_expr(self.ast.builder());
+        let outer = self.filter.clone();
+        self.filter = Some(match outer.clone() {
+            Some(outer) => outer.and(cond),
+            None => cond,
+        });
+        let res = f(self);
+        self.filter = outer;
+        res
+    }
+
     /// Filter the rows of this sub-query based on a value from the outer query.
     pub fn filter_on<T: EqTyp + 'static>(
         &mut self,
@@ -74,36 +145,499 @@ impl<'outer, 'inner, S: 'static> Aggregate<'outer, 'inner, S> {
             .push(Box::new((val.build_expr(self.ast.builder()), alias)))
     }
 
+    /// Same as [Aggregate::filter_on], but for an optional `val`.
+    ///
+    /// Rows of the sub-query where `val` is [None] are skipped.
+    /// This is a shorthand for [crate::Rows::filter_some] followed by [Aggregate::filter_on].
+    /// ```
+    /// # use rust_query::{aggregate, migration::{schema, Config}, Database, LocalClient, Table, TableRow};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Employee { name: String, reports_to: Option<Employee> },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// let boss = txn.insert(Employee { name: "Boss", reports_to: None::<TableRow<Employee>> });
+    /// txn.insert(Employee { name: "Worker", reports_to: Some(boss) });
+    /// let reports = txn.query(|rows| {
+    ///     let receiver = Employee::join(rows);
+    ///     let count = aggregate(|rows| {
+    ///         let reporter = Employee::join(rows);
+    ///         rows.filter_on_some(reporter.reports_to(), &receiver);
+    ///         rows.count_distinct(reporter)
+    ///     });
+    ///     rows.into_vec((receiver.name(), count))
+    /// });
+    /// assert_eq!(reports, [("Boss".to_owned(), 1), ("Worker".to_owned(), 0)]);
+    /// ```
+    pub fn filter_on_some<T: EqTyp + 'static>(
+        &mut self,
+        val: impl IntoColumn<'inner, S, Typ = Option<T>>,
+        on: impl IntoColumn<'outer, S, Typ = T>,
+    ) {
+        let val = self.filter_some(val);
+        self.filter_on(val, on)
+    }
+
     /// Return the average value in a column, this is [None] if there are zero rows.
     pub fn avg(
         &self,
         val: impl IntoColumn<'inner, S, Typ = f64>,
     ) -> Column<'outer, S, Option<f64>> {
         let val = val.into_column().inner;
-        let expr = Func::avg(val.build_expr(self.ast.builder()));
+        let expr = self.filter_call(Func::avg(val.build_expr(self.ast.builder())));
+        Column::new(self.select(expr))
+    }
+
+    /// Return the average of an integer column multiplied by `scale`, this is [None] if there are zero rows.
+    ///
+    /// The average is calculated as `sum * scale / count` using only integer arithmetic,
+    /// so there is no floating point rounding. The result is rounded towards zero.
+    /// For example, with a `scale` of `100` the average of amounts in cents is in hundredths of a cent.
+    ///
+    /// `NULL` values are ignored.
+    /// The query panics if `sum * scale` overflows an [i64].
+    /// ```
+    /// # use rust_query::{aggregate, migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Payment { cents: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// for cents in [100, 100, 101] {
+    ///     txn.insert(Payment { cents });
+    /// }
+    /// let avg = txn.query_one(aggregate(|rows| {
+    ///     let payment = Payment::join(rows);
+    ///     rows.avg_int_scaled(payment.cents(), 100)
+    /// }));
+    /// assert_eq!(avg, Some(10033));
+    /// ```
+    pub fn avg_int_scaled(
+        &self,
+        val: impl IntoColumn<'inner, S, Typ = i64>,
+        scale: i64,
+    ) -> Column<'outer, S, Option<i64>> {
+        let val = val.into_column().inner.build_expr(self.ast.builder());
+        let sum = Expr::expr(self.filter_call(Func::sum(val.clone()))).mul(scale);
+        let expr = sum.div(self.filter_call(Func::count(val)));
+        Column::new(self.select(expr))
+    }
+
+    /// Return the minimum value in a column, this is [None] if there are zero rows.
+    ///
+    /// This works for all column types that can be ordered, strings are compared using the `BINARY` collation.
+    /// ```
+    /// # use rust_query::{aggregate, migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Genre { name: String },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// for name in ["Rock", "Jazz", "Pop"] {
+    ///     txn.insert(Genre { name });
+    /// }
+    /// let (first, last) = txn.query_one(aggregate(|rows| {
+    ///     let genre = Genre::join(rows);
+    ///     (rows.min(genre.name()), rows.max(genre.name()))
+    /// }));
+    /// assert_eq!(first.as_deref(), Some("Jazz"));
+    /// assert_eq!(last.as_deref(), Some("Rock"));
+    /// ```
+    pub fn min<T>(&self, val: impl IntoColumn<'inner, S, Typ = T>) -> Column<'outer, S, Option<T>>
+    where
+        T: OrdTyp + MyTyp,
+    {
+        let val = val.into_column().inner;
+        let expr = self.filter_call(Func::min(val.build_expr(self.ast.builder())));
         Column::new(self.select(expr))
     }
 
     /// Return the maximum value in a column, this is [None] if there are zero rows.
+    ///
+    /// See [Aggregate::min] for more information.
     pub fn max<T>(&self, val: impl IntoColumn<'inner, S, Typ = T>) -> Column<'outer, S, Option<T>>
     where
-        T: NumTyp,
+        T: OrdTyp + MyTyp,
     {
         let val = val.into_column().inner;
-        let expr = Func::max(val.build_expr(self.ast.builder()));
+        let expr = self.filter_call(Func::max(val.build_expr(self.ast.builder())));
         Column::new(self.select(expr))
     }
 
     /// Return the sum of a column.
+    ///
+    /// The sum of an integer column is an integer, so there is no precision loss.
+    /// The query panics if the sum of an integer column overflows an [i64].
+    /// Use [Aggregate::total] if the sum might not fit, or [Aggregate::sum_int] to make sure that the result is an integer.
     pub fn sum<T>(&self, val: impl IntoColumn<'inner, S, Typ = T>) -> Column<'outer, S, T>
     where
         T: NumTyp,
     {
         let val = val.into_column().inner;
-        let expr = Func::sum(val.build_expr(self.ast.builder()));
+        let expr = self.filter_call(Func::sum(val.build_expr(self.ast.builder())));
         Column::new(UnwrapOr(self.select::<T>(expr), Const(T::ZERO)))
     }
 
+    /// Return the sum of an integer column as an [i64].
+    ///
+    /// This is the same as [Aggregate::sum], but only accepts integer columns.
+    /// The query panics if the sum overflows an [i64], it is never converted to a floating point number.
+    /// ```
+    /// # use rust_query::{aggregate, migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Payment { cents: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// txn.insert(Payment { cents: 9_007_199_254_740_993 });
+    /// txn.insert(Payment { cents: 1 });
+    /// let sum = txn.query_one(aggregate(|rows| {
+    ///     let payment = Payment::join(rows);
+    ///     rows.sum_int(payment.cents())
+    /// }));
+    /// assert_eq!(sum, 9_007_199_254_740_994);
+    /// ```
+    pub fn sum_int(&self, val: impl IntoColumn<'inner, S, Typ = i64>) -> Column<'outer, S, i64> {
+        self.sum(val)
+    }
+
+    /// Return the sum of a column as a floating point number.
+    ///
+    /// This uses the sqlite `total` function, which returns `0.0` when there are no rows.
+    /// Unlike [Aggregate::sum] it never fails with an integer overflow.
+    pub fn total<T>(&self, val: impl IntoColumn<'inner, S, Typ = T>) -> Column<'outer, S, f64>
+    where
+        T: NumTyp,
+    {
+        let val = val.into_column().inner;
+        let expr = Func::cust(Alias::new("total")).arg(val.build_expr(self.ast.builder()));
+        let expr = self.filter_call(expr);
+        Column::new(UnwrapOr(self.select::<f64>(expr), Const(0.)))
+    }
+
+    /// Concatenate the values of a column, with `separator` between the values.
+    ///
+    /// This uses the sqlite `group_concat` function, it returns an empty string when there are zero rows.
+    /// The order of the values is arbitrary, use [Aggregate::group_concat_ordered] to choose the order.
+    pub fn group_concat(
+        &self,
+        val: impl IntoColumn<'inner, S, Typ = String>,
+        separator: &str,
+    ) -> Column<'outer, S, String> {
+        let val = val.into_column().inner.build_expr(self.ast.builder());
+        let expr = Func::cust(Alias::new("group_concat")).args([val, separator.into()]);
+        let expr = self.filter_call(expr);
+        Column::new(UnwrapOr(self.select::<String>(expr), String::new()))
+    }
+
+    /// Same as [Aggregate::group_concat], but the values are concatenated in the order of `order_by`.
+    ///
+    /// The values are concatenated with `group_concat` as a window function that is ordered by `order_by`.
+    /// This gives the same result as `group_concat(val, separator ORDER BY order_by)`,
+    /// but it does not require sqlite 3.44 or later.
+    /// Values with the same `order_by` value are concatenated in an arbitrary order.
+    /// ```
+    /// # use rust_query::{aggregate, migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Track { name: String, milliseconds: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// txn.insert(Track { name: "b", milliseconds: 100 });
+    /// txn.insert(Track { name: "c", milliseconds: 300 });
+    /// txn.insert(Track { name: "a", milliseconds: 200 });
+    /// let (by_name, by_length) = txn.query_one(aggregate(|rows| {
+    ///     let track = Track::join(rows);
+    ///     (
+    ///         rows.group_concat_ordered(track.name(), ", ", track.name()),
+    ///         rows.group_concat_ordered(track.name(), ", ", track.milliseconds()),
+    ///     )
+    /// }));
+    /// assert_eq!(by_name, "a, b, c");
+    /// assert_eq!(by_length, "b, a, c");
+    /// ```
+    pub fn group_concat_ordered<O: 'static>(
+        &self,
+        val: impl IntoColumn<'inner, S, Typ = String>,
+        separator: &str,
+        order_by: impl IntoColumn<'inner, S, Typ = O>,
+    ) -> Column<'outer, S, String> {
+        // building the expressions can add implicit joins, so this is done first
+        let val = val.into_column().inner.build_expr(self.ast.builder());
+        let order_by = order_by.into_column().inner.build_expr(self.ast.builder());
+
+        let mut inner = self.build_filtered_source();
+        let mut concat_window = WindowStatement::new();
+        let mut rank_window = WindowStatement::new();
+        for (group, alias) in self.ast.filter_on.iter() {
+            inner.expr_as(group.clone(), *alias);
+            concat_window.add_partition_by(group.clone());
+            rank_window.add_partition_by(group.clone());
+        }
+        concat_window.order_by_expr(order_by, Order::Asc);
+        concat_window.frame_between(
+            FrameType::Rows,
+            Frame::UnboundedPreceding,
+            Frame::UnboundedFollowing,
+        );
+
+        let [value, rank] = [(); 2].map(|_| self.ast.scope.new_alias());
+        let concat = Func::cust(Alias::new("group_concat")).args([val, separator.into()]);
+        inner.expr_window_as(concat, concat_window, value);
+        inner.expr_window_as(Expr::cust("ROW_NUMBER()"), rank_window, rank);
+
+        // every row of a group has the same value, so only one is kept
+        let table = self.ast.scope.new_alias();
+        let mut select = SelectStatement::new();
+        select.from_subquery(inner, table);
+        select.and_where(Expr::col((table, rank)).eq(1));
+        for (_, alias) in self.ast.filter_on.iter() {
+            select.expr_as(Expr::col((table, *alias)), *alias);
+        }
+        let field = self.ast.scope.new_field();
+        select.expr_as(Expr::col((table, value)), field);
+
+        let concat = Aggr::<S, Option<String>> {
+            _p2: PhantomData,
+            select,
+            field,
+            conds: self.conds.clone(),
+        };
+        Column::new(UnwrapOr(concat, String::new()))
+    }
+
+    /// Return the percentile `p` of a column, this is [None] if there are zero rows.
+    ///
+    /// The percentile is calculated using linear interpolation between the closest ranks.
+    /// The sorted values are numbered from `0` to `n - 1`, and the result is the value at
+    /// position `p * (n - 1)`. If this position is between two values, then the result is
+    /// interpolated between them. This is the same method as the default of `numpy.percentile`.
+    ///
+    /// `NULL` values are ignored. This uses the sqlite window functions `ROW_NUMBER` and `COUNT`.
+    ///
+    /// Panics if `p` is not between `0.0` and `1.0`.
+    pub fn percentile<T>(
+        &self,
+        val: impl IntoColumn<'inner, S, Typ = T>,
+        p: f64,
+    ) -> Column<'outer, S, Option<f64>>
+    where
+        T: NumTyp,
+    {
+        assert!(
+            (0.0..=1.0).contains(&p),
+            "percentile must be between 0 and 1"
+        );
+        let val = val.into_column().inner;
+        // building the expression can add implicit joins, so this is done first
+        let val = val.build_expr(self.ast.builder());
+
+        let mut inner = self.build_filtered_source();
+        inner.and_where(Expr::expr(val.clone()).is_not_null());
+        let mut rank_window = WindowStatement::new();
+        let mut count_window = WindowStatement::new();
+        for (group, alias) in self.ast.filter_on.iter() {
+            inner.expr_as(group.clone(), *alias);
+            rank_window.add_partition_by(group.clone());
+            count_window.add_partition_by(group.clone());
+        }
+        rank_window.order_by_expr(val.clone(), Order::Asc);
+
+        let [value, rank, count] = [(); 3].map(|_| self.ast.scope.new_alias());
+        inner.expr_as(val, value);
+        inner.expr_window_as(Expr::cust("ROW_NUMBER()"), rank_window, rank);
+        inner.expr_window_as(Func::count(Expr::col(Asterisk)), count_window, count);
+
+        let table = self.ast.scope.new_alias();
+        let mut select = SelectStatement::new();
+        select.from_subquery(inner, table);
+        for (_, alias) in self.ast.filter_on.iter() {
+            select.expr_as(Expr::col((table, *alias)), *alias);
+            select.add_group_by([Expr::col((table, *alias)).into()]);
+        }
+
+        // every value gets a weight based on the distance between its rank and the position
+        let pos = Expr::col((table, count)).sub(1).mul(p);
+        let dist = Func::abs(Expr::col((table, rank)).sub(1).sub(pos));
+        let weight =
+            Func::cust(Alias::new("max")).args([Expr::val(0.).into(), Expr::val(1.).sub(dist)]);
+        let field = self.ast.scope.new_field();
+        select.expr_as(Func::sum(Expr::col((table, value)).mul(weight)), field);
+
+        Column::new(Aggr::<S, Option<f64>> {
+            _p2: PhantomData,
+            select,
+            field,
+            conds: self.conds.clone(),
+        })
+    }
+
+    /// Return the median of a column, this is [None] if there are zero rows.
+    ///
+    /// If there is an even number of values, then this is the average of the middle two values.
+    /// See [Aggregate::percentile] for more information.
+    /// ```
+    /// # use rust_query::{aggregate, migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Track { milliseconds: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// for milliseconds in [4, 1, 3, 10] {
+    ///     txn.insert(Track { milliseconds });
+    /// }
+    /// let median = txn.query_one(aggregate(|rows| {
+    ///     let track = Track::join(rows);
+    ///     rows.median(track.milliseconds())
+    /// }));
+    /// assert_eq!(median, Some(3.5));
+    /// ```
+    pub fn median<T>(
+        &self,
+        val: impl IntoColumn<'inner, S, Typ = T>,
+    ) -> Column<'outer, S, Option<f64>>
+    where
+        T: NumTyp,
+    {
+        self.percentile(val, 0.5)
+    }
+
+    /// Return the value of a column in the first row when ordered by `order_by`,
+    /// this is [None] if there are zero rows.
+    ///
+    /// This is like a sub-query with `ORDER BY order_by LIMIT 1` for every outer row.
+    /// Unlike joining the rows, this never returns more than one value per outer row.
+    /// If multiple rows have the same smallest `order_by` value, then one of them is picked arbitrarily.
+    ///
+    /// This uses the sqlite window function `ROW_NUMBER`.
+    /// ```
+    /// # use rust_query::{aggregate, migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Customer { name: String },
+    /// #     Invoice { customer: Customer, date: i64, total: f64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// # let alice = txn.insert(Customer { name: "Alice" });
+    /// # txn.insert(Customer { name: "Bob" });
+    /// # txn.insert(Invoice { customer: alice, date: 2, total: 10. });
+    /// # txn.insert(Invoice { customer: alice, date: 1, total: 5. });
+    /// let first_totals = txn.query(|rows| {
+    ///     let customer = Customer::join(rows);
+    ///     let first_total = aggregate(|rows| {
+    ///         let invoice = Invoice::join(rows);
+    ///         rows.filter_on(invoice.customer(), &customer);
+    ///         rows.first(invoice.total(), invoice.date())
+    ///     });
+    ///     rows.into_vec((customer.name(), first_total))
+    /// });
+    /// assert_eq!(first_totals, [("Alice".to_owned(), Some(5.)), ("Bob".to_owned(), None)]);
+    /// ```
+    pub fn first<T: MyTyp, O: 'static>(
+        &self,
+        val: impl IntoColumn<'inner, S, Typ = T>,
+        order_by: impl IntoColumn<'inner, S, Typ = O>,
+    ) -> Column<'outer, S, Option<T>> {
+        self.pick(val, order_by, Order::Asc)
+    }
+
+    /// Return the value of a column in the last row when ordered by `order_by`,
+    /// this is [None] if there are zero rows.
+    ///
+    /// This is like [Aggregate::first], but with a descending order.
+    pub fn last<T: MyTyp, O: 'static>(
+        &self,
+        val: impl IntoColumn<'inner, S, Typ = T>,
+        order_by: impl IntoColumn<'inner, S, Typ = O>,
+    ) -> Column<'outer, S, Option<T>> {
+        self.pick(val, order_by, Order::Desc)
+    }
+
+    fn pick<T: MyTyp, O: 'static>(
+        &self,
+        val: impl IntoColumn<'inner, S, Typ = T>,
+        order_by: impl IntoColumn<'inner, S, Typ = O>,
+        order: Order,
+    ) -> Column<'outer, S, Option<T>> {
+        // building the expressions can add implicit joins, so this is done first
+        let val = val.into_column().inner.build_expr(self.ast.builder());
+        let order_by = order_by.into_column().inner.build_expr(self.ast.builder());
+
+        let mut inner = self.build_filtered_source();
+        let mut rank_window = WindowStatement::new();
+        for (group, alias) in self.ast.filter_on.iter() {
+            inner.expr_as(group.clone(), *alias);
+            rank_window.add_partition_by(group.clone());
+        }
+        rank_window.order_by_expr(order_by, order);
+
+        let [value, rank] = [(); 2].map(|_| self.ast.scope.new_alias());
+        inner.expr_as(val, value);
+        inner.expr_window_as(Expr::cust("ROW_NUMBER()"), rank_window, rank);
+
+        // there is exactly one row left for every group
+        let table = self.ast.scope.new_alias();
+        let mut select = SelectStatement::new();
+        select.from_subquery(inner, table);
+        select.and_where(Expr::col((table, rank)).eq(1));
+        for (_, alias) in self.ast.filter_on.iter() {
+            select.expr_as(Expr::col((table, *alias)), *alias);
+        }
+        let field = self.ast.scope.new_field();
+        select.expr_as(Expr::col((table, value)), field);
+
+        Column::new(Aggr::<S, Option<T>> {
+            _p2: PhantomData,
+            select,
+            field,
+            conds: self.conds.clone(),
+        })
+    }
+
+    /// Return the number of rows.
+    ///
+    /// There are three different ways to count:
+    /// - [Aggregate::count] counts all rows.
+    /// - [Aggregate::count_col] counts the rows where the column is not `NULL`.
+    /// - [Aggregate::count_distinct] counts the number of different values in a column.
+    pub fn count(&self) -> Column<'outer, S, i64> {
+        let expr = self.filter_call(Func::count(Expr::col(Asterisk)));
+        Column::new(UnwrapOr(self.select::<i64>(expr), Const(0)))
+    }
+
+    /// Return the number of rows where the column is not `NULL`.
+    ///
+    /// For columns that are not optional this is the same as [Aggregate::count].
+    pub fn count_col<T: 'static>(
+        &self,
+        val: impl IntoColumn<'inner, S, Typ = T>,
+    ) -> Column<'outer, S, i64> {
+        let val = val.into_column().inner;
+        let expr = self.filter_call(Func::count(val.build_expr(self.ast.builder())));
+        Column::new(UnwrapOr(self.select::<i64>(expr), Const(0)))
+    }
+
     /// Return the number of distinct values in a column.
     pub fn count_distinct<T: 'static>(
         &self,
@@ -113,21 +647,22 @@ impl<'outer, 'inner, S: 'static> Aggregate<'outer, 'inner, S> {
         T: EqTyp,
     {
         let val = val.into_column().inner;
-        let expr = Func::count_distinct(val.build_expr(self.ast.builder()));
+        let expr = self.filter_call(Func::count_distinct(val.build_expr(self.ast.builder())));
         Column::new(UnwrapOr(self.select::<i64>(expr), Const(0)))
     }
 
     /// Return whether there are any rows.
     pub fn exists(&self) -> Column<'outer, S, bool> {
-        let expr = SimpleExpr::Constant(1.into_sea_value());
-        Column::new(IsNotNull(self.select::<i64>(expr)))
+        // Without `filter_on` there is no `GROUP BY`, so the sub-query always has one row.
+        let expr = Expr::expr(self.filter_call(Func::count(Expr::col(Asterisk)))).gt(0);
+        Column::new(UnwrapOr(self.select::<bool>(expr), false))
     }
 }
 
 pub struct Aggr<S, T> {
     pub(crate) _p2: PhantomData<(S, T)>,
     pub(crate) select: SelectStatement,
-    pub(crate) conds: Vec<(Field, Rc<dyn Fn(ValueBuilder) -> SimpleExpr>)>,
+    pub(crate) conds: Vec<(Field, OuterExpr)>,
     pub(crate) field: Field,
 }
 
@@ -168,6 +703,69 @@ impl<S, T: Table> Deref for Aggr<S, T> {
 ///
 /// You can filter the rows in the aggregate based on values from the outer query.
 /// That is the only way to get a different aggregate for each outer row.
+///
+/// The aggregate always has a result, even if there are no rows to aggregate.
+/// In that case the aggregate functions return their value for zero rows.
+/// This makes it possible to use [crate::Transaction::query_one] with an aggregate on an empty table.
+/// ```
+/// # use rust_query::{aggregate, migration::{schema, Config}, Database, LocalClient, Table};
+/// # #[schema]
+/// # enum Schema {
+/// #     Invoice { total: f64 },
+/// # }
+/// # use v0::*;
+/// # let mut client = LocalClient::try_new().unwrap();
+/// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+/// # let txn = client.transaction(&database);
+/// let (sum, avg) = txn.query_one(aggregate(|rows| {
+///     let invoice = Invoice::join(rows);
+///     (rows.sum(invoice.total()), rows.avg(invoice.total()))
+/// }));
+/// assert_eq!(sum, 0.);
+/// assert_eq!(avg, None);
+/// ```
+///
+/// # Filtering on aggregate results
+///
+/// There is no `HAVING` clause, because it would not do what you expect.
+/// The aggregate is joined with the outer query using a `LEFT JOIN`, so an outer row whose group
+/// is filtered out would still be returned, only with the aggregate values for zero rows.
+/// Instead, filter the outer query on the result of the aggregate.
+///
+/// An empty group gives the value for zero rows, which is [None] for aggregates like
+/// [Aggregate::avg] and [Aggregate::last].
+/// Using [crate::Rows::filter_some] on such an aggregate removes the outer rows with an empty group,
+/// so the unwrapped column can not be `NULL` in the rows that are returned.
+/// ```
+/// # use rust_query::{aggregate, migration::{schema, Config}, Database, LocalClient, Table};
+/// # #[schema]
+/// # enum Schema {
+/// #     Genre { name: String },
+/// #     Track { genre: Genre, milliseconds: i64 },
+/// # }
+/// # use v0::*;
+/// # let mut client = LocalClient::try_new().unwrap();
+/// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+/// # let mut txn = client.transaction_mut(&database);
+/// # let rock = txn.insert(Genre { name: "Rock" });
+/// # let jazz = txn.insert(Genre { name: "Jazz" });
+/// # txn.insert(Genre { name: "Blues" });
+/// # txn.insert(Track { genre: rock, milliseconds: 200_000 });
+/// # txn.insert(Track { genre: jazz, milliseconds: 400_000 });
+/// let long_genres = txn.query(|rows| {
+///     let genre = Genre::join(rows);
+///     let avg_length = aggregate(|rows| {
+///         let track = Track::join(rows);
+///         rows.filter_on(track.genre(), &genre);
+///         rows.avg(track.milliseconds().as_float())
+///     });
+///     // genres without tracks have no average, they are removed here
+///     let avg_length = rows.filter_some(avg_length);
+///     rows.filter(avg_length.lt(300_000.).not());
+///     rows.into_vec(genre.name())
+/// });
+/// assert_eq!(long_genres, ["Jazz"]);
+/// ```
 pub fn aggregate<'outer, S, F, R>(f: F) -> R
 where
     F: for<'inner> FnOnce(&mut Aggregate<'outer, 'inner, S>) -> R,
@@ -181,7 +779,165 @@ where
     let mut group = Aggregate {
         conds: Vec::new(),
         query: inner,
+        filter: None,
         phantom2: PhantomData,
     };
     f(&mut group)
 }
+
+/// Check whether there are any rows in a sub-query.
+///
+/// This works like [aggregate] and you can use [Exists::filter_on] to filter the rows
+/// based on values from the outer query.
+/// Unlike [Aggregate::exists] this does not count the rows, sqlite stops looking as soon as
+/// it finds a matching row.
+///
+/// Without [Exists::filter_on] this uses `EXISTS (SELECT ...)`, otherwise it uses
+/// `(...) IN (SELECT ...)` with the values that are filtered on.
+/// ```
+/// # use rust_query::{exists, migration::{schema, Config}, Database, LocalClient, Table};
+/// # #[schema]
+/// # enum Schema {
+/// #     Customer { name: String },
+/// #     Invoice { customer: Customer, total: f64 },
+/// # }
+/// # use v0::*;
+/// # let mut client = LocalClient::try_new().unwrap();
+/// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+/// # let mut txn = client.transaction_mut(&database);
+/// # let alice = txn.insert(Customer { name: "Alice" });
+/// # txn.insert(Customer { name: "Bob" });
+/// # txn.insert(Invoice { customer: alice, total: 10. });
+/// let with_invoice = txn.query(|rows| {
+///     let customer = Customer::join(rows);
+///     rows.filter(exists(|rows| {
+///         let invoice = Invoice::join(rows);
+///         rows.filter_on(invoice.customer(), &customer);
+///     }));
+///     rows.into_vec(customer.name())
+/// });
+/// assert_eq!(with_invoice, ["Alice"]);
+/// ```
+pub fn exists<'outer, S, F>(f: F) -> Column<'outer, S, bool>
+where
+    F: for<'inner> FnOnce(&mut Exists<'outer, 'inner, S>),
+{
+    let mut exists = Exists {
+        inner: Aggregate {
+            conds: Vec::new(),
+            query: Rows {
+                phantom: PhantomData,
+                ast: MySelect::default(),
+                _p: PhantomData,
+            },
+            filter: None,
+            phantom2: PhantomData,
+        },
+    };
+    f(&mut exists);
+    let group = exists.inner;
+    Column::new(ExistsExpr {
+        select: group.query.ast.build_filter_on(),
+        conds: group.conds.into_iter().map(|(_, cond)| cond).collect(),
+    })
+}
+
+/// Check whether there are no rows in a sub-query, this is also called an anti-join.
+///
+/// This is the opposite of [exists] and it uses `NOT EXISTS (SELECT ...)` or `NOT (...) IN (SELECT ...)`.
+/// Unlike an [aggregate] that counts the rows, sqlite stops looking as soon as it finds a matching row.
+/// ```
+/// # use rust_query::{not_exists, migration::{schema, Config}, Database, LocalClient, Table};
+/// # #[schema]
+/// # enum Schema {
+/// #     Artist { name: String },
+/// #     Album { artist: Artist, title: String },
+/// # }
+/// # use v0::*;
+/// # let mut client = LocalClient::try_new().unwrap();
+/// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+/// # let mut txn = client.transaction_mut(&database);
+/// # let queen = txn.insert(Artist { name: "Queen" });
+/// # txn.insert(Artist { name: "Nobody" });
+/// # txn.insert(Album { artist: queen, title: "Jazz" });
+/// let without_album = txn.query(|rows| {
+///     let artist = Artist::join(rows);
+///     rows.filter(not_exists(|rows| {
+///         let album = Album::join(rows);
+///         rows.filter_on(album.artist(), &artist);
+///     }));
+///     rows.into_vec(artist.name())
+/// });
+/// assert_eq!(without_album, ["Nobody"]);
+/// ```
+pub fn not_exists<'outer, S, F>(f: F) -> Column<'outer, S, bool>
+where
+    F: for<'inner> FnOnce(&mut Exists<'outer, 'inner, S>),
+{
+    exists(f).not()
+}
+
+/// This is the argument type used by [exists] and [not_exists].
+///
+/// Tables can be joined and rows can be filtered like with [crate::Rows].
+/// Use [Exists::filter_on] to filter the rows based on values from the outer query.
+/// Unlike [Aggregate] there are no aggregate functions, because only the existence of rows is checked.
+pub struct Exists<'outer, 'inner, S> {
+    inner: Aggregate<'outer, 'inner, S>,
+}
+
+impl<'outer, 'inner, S> Deref for Exists<'outer, 'inner, S> {
+    type Target = Rows<'inner, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner.query
+    }
+}
+
+impl<'outer, 'inner, S> DerefMut for Exists<'outer, 'inner, S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner.query
+    }
+}
+
+impl<'outer, 'inner, S: 'static> Exists<'outer, 'inner, S> {
+    /// Filter the rows of this sub-query based on a value from the outer query.
+    ///
+    /// See [Aggregate::filter_on] for more information.
+    pub fn filter_on<T: EqTyp + 'static>(
+        &mut self,
+        val: impl IntoColumn<'inner, S, Typ = T>,
+        on: impl IntoColumn<'outer, S, Typ = T>,
+    ) {
+        self.inner.filter_on(val, on)
+    }
+
+    /// Same as [Exists::filter_on], but for an optional `val`.
+    ///
+    /// See [Aggregate::filter_on_some] for more information.
+    pub fn filter_on_some<T: EqTyp + 'static>(
+        &mut self,
+        val: impl IntoColumn<'inner, S, Typ = Option<T>>,
+        on: impl IntoColumn<'outer, S, Typ = T>,
+    ) {
+        self.inner.filter_on_some(val, on)
+    }
+}
+
+#[derive(Clone)]
+struct ExistsExpr {
+    select: SelectStatement,
+    conds: Vec<OuterExpr>,
+}
+
+impl Typed for ExistsExpr {
+    type Typ = bool;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        if self.conds.is_empty() {
+            return Expr::exists(self.select.clone());
+        }
+        // the sub-query does not refer to the outer query, because the aliases could overlap
+        let outer = self.conds.iter().map(|cond| cond(b));
+        Expr::tuple(outer).in_subquery(self.select.clone())
+    }
+}
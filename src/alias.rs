@@ -13,6 +13,14 @@ pub struct Scope {
     iden_num: AtomicU64,
 }
 
+impl Clone for Scope {
+    fn clone(&self) -> Self {
+        Self {
+            iden_num: AtomicU64::new(self.iden_num.load(Ordering::Relaxed)),
+        }
+    }
+}
+
 impl Scope {
     pub fn tmp_table(&self) -> TmpTable {
         let next = self.iden_num.fetch_add(1, Ordering::Relaxed);
@@ -1,5 +1,5 @@
 use elsa::FrozenVec;
-use sea_query::{Alias, Asterisk, Condition, Expr, NullAlias, SelectStatement, SimpleExpr};
+use sea_query::{Alias, Asterisk, Condition, Expr, Iden, NullAlias, SelectStatement, SimpleExpr};
 
 use crate::{
     alias::{Field, MyAlias, RawAlias, Scope},
@@ -12,6 +12,8 @@ pub struct MySelect {
     pub(super) scope: Scope,
     // tables to join, adding more requires mutating
     pub(super) tables: Vec<(String, MyAlias)>,
+    // sub-queries to join, these are also joined like tables
+    pub(super) subqueries: Vec<(SelectStatement, MyAlias)>,
     // implicit joins
     pub(super) extra: MyMap<Source, MyAlias>,
     // all conditions to check
@@ -22,12 +24,13 @@ pub struct MySelect {
     pub(super) filter_on: FrozenVec<Box<(SimpleExpr, MyAlias)>>,
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone)]
 pub(super) struct Source {
     pub(super) conds: Vec<(Field, SimpleExpr)>,
     pub(super) kind: SourceKind,
 }
 
+#[derive(Clone)]
 pub(super) enum SourceKind {
     Aggregate(SelectStatement),
     // table and pk
@@ -44,6 +47,30 @@ impl PartialEq for SourceKind {
     }
 }
 
+impl Clone for MySelect {
+    fn clone(&self) -> Self {
+        let copy = Self {
+            scope: self.scope.clone(),
+            tables: self.tables.clone(),
+            subqueries: self.subqueries.clone(),
+            ..Default::default()
+        };
+        for item in self.extra.iter() {
+            copy.extra.push(Box::new(item.clone()));
+        }
+        for item in self.filters.iter() {
+            copy.filters.push(Box::new(item.clone()));
+        }
+        for item in self.select.iter() {
+            copy.select.push(Box::new(item.clone()));
+        }
+        for item in self.filter_on.iter() {
+            copy.filter_on.push(Box::new(item.clone()));
+        }
+        copy
+    }
+}
+
 impl MySelect {
     pub fn builder(&self) -> ValueBuilder<'_> {
         ValueBuilder { inner: self }
@@ -68,7 +95,80 @@ impl MySelect {
         select
     }
 
+    /// Build one part of a compound select.
+    ///
+    /// The parts of a compound select need to have the same columns in the same order.
+    /// That is why this does not deduplicate columns like [MySelect::cache] does.
+    pub fn build_compound_part(
+        &self,
+        exprs: impl IntoIterator<Item = DynTypedExpr>,
+    ) -> (SelectStatement, Vec<Field>) {
+        // building the expressions can add implicit joins, so we do this first
+        let exprs: Vec<_> = exprs
+            .into_iter()
+            .map(|val| (val.0)(self.builder()))
+            .collect();
+        let mut select = self.build_source();
+
+        let mut fields = vec![];
+        for expr in exprs {
+            let field = self.scope.new_field();
+            select.expr_as(expr, field);
+            fields.push(field);
+        }
+        if fields.is_empty() {
+            select.expr_as(Expr::val(1), NullAlias);
+        }
+        (select, fields)
+    }
+
     pub fn build_select(&self, is_group: bool) -> SelectStatement {
+        let mut select = self.build_source();
+
+        let mut any_expr = false;
+        let mut any_group = false;
+        for (group, alias) in self.filter_on.iter() {
+            any_expr = true;
+
+            select.expr_as(group.clone(), *alias);
+            if is_group {
+                any_group = true;
+                select.add_group_by([group.clone()]);
+            }
+        }
+
+        for (aggr, alias) in self.select.iter() {
+            any_expr = true;
+            select.expr_as(aggr.clone(), *alias);
+        }
+
+        if !any_expr {
+            select.expr_as(Expr::val(1), NullAlias);
+        }
+
+        if !any_group && is_group {
+            select.expr_as(Expr::count(Expr::col(Asterisk)), NullAlias);
+        }
+
+        select
+    }
+
+    /// Build a select that returns the values that are filtered on.
+    ///
+    /// Unlike [MySelect::build_select] this does not include the aggregate results.
+    pub fn build_filter_on(&self) -> SelectStatement {
+        let mut select = self.build_source();
+        for (group, alias) in self.filter_on.iter() {
+            select.expr_as(group.clone(), *alias);
+        }
+        if self.filter_on.is_empty() {
+            select.expr_as(Expr::val(1), NullAlias);
+        }
+        select
+    }
+
+    /// Build the `FROM`, `JOIN` and `WHERE` clauses.
+    pub fn build_source(&self) -> SelectStatement {
         let mut select = SelectStatement::new();
 
         let mut any_from = false;
@@ -78,6 +178,11 @@ impl MySelect {
             any_from = true
         }
 
+        for (subquery, alias) in &self.subqueries {
+            select.from_subquery(subquery.clone(), *alias);
+            any_from = true
+        }
+
         if !any_from {
             select.from_values([1], NullAlias);
         }
@@ -108,31 +213,29 @@ impl MySelect {
             select.and_where(filter.clone());
         }
 
-        let mut any_expr = false;
-        let mut any_group = false;
-        for (group, alias) in self.filter_on.iter() {
-            any_expr = true;
-
-            select.expr_as(group.clone(), *alias);
-            if is_group {
-                any_group = true;
-                select.add_group_by([group.clone()]);
-            }
-        }
-
-        for (aggr, alias) in self.select.iter() {
-            any_expr = true;
-            select.expr_as(aggr.clone(), *alias);
-        }
+        select
+    }
 
-        if !any_expr {
-            select.expr_as(Expr::val(1), NullAlias);
+    /// Build the `RETURNING` clause of an insert or update statement.
+    ///
+    /// `RETURNING` can not join other tables, so if there are any joins,
+    /// every column is retrieved with a subquery that has the joins.
+    pub fn build_returning(&self) -> Vec<SimpleExpr> {
+        let mut res = vec![];
+        for (expr, field) in self.select.iter() {
+            let expr = if self.extra.is_empty() {
+                expr.clone()
+            } else {
+                let mut select = self.build_source();
+                select.expr(expr.clone());
+                SimpleExpr::SubQuery(None, Box::new(select.into_sub_query_statement()))
+            };
+            let alias = field.to_string();
+            res.push(Expr::cust_with_expr(format!("? AS \"{alias}\""), expr));
         }
-
-        if !any_group && is_group {
-            select.expr_as(Expr::count(Expr::col(Asterisk)), NullAlias);
+        if res.is_empty() {
+            res.push(Expr::val(1).into());
         }
-
-        select
+        res
     }
 }
@@ -1,8 +1,8 @@
 use std::cell::Cell;
 
-use rusqlite::Connection;
+use rusqlite::{Connection, TransactionBehavior};
 
-use crate::{Database, Transaction, TransactionMut};
+use crate::{Database, Transaction, TransactionMut, TransactionReadonly};
 
 /// The primary interface to the database.
 ///
@@ -19,17 +19,32 @@ pub struct LocalClient {
 impl LocalClient {
     /// Create a [Transaction]. This operation always completes immediately as it does not need to wait on other transactions.
     ///
+    /// The [Transaction] reads from a single snapshot of the database for its whole lifetime.
+    /// Create a new [Transaction] to see the changes of [TransactionMut]s committed in the mean time.
+    ///
     /// This function will panic if the schema was modified compared to when the [Database] value
     /// was created. This can happen for example by running another instance of your program with
     /// additional migrations.
     pub fn transaction<S>(&mut self, db: &Database<S>) -> Transaction<S> {
-        use r2d2::ManageConnection;
-        // TODO: could check here if the existing connection is good to use.
-        let conn = self.conn.insert(db.manager.connect().unwrap());
+        let conn = self.connection(db);
         let txn = conn.transaction().unwrap();
         Transaction::new_checked(txn, db.schema_version)
     }
 
+    /// Same as [LocalClient::transaction], but sqlite makes sure that nothing is written.
+    ///
+    /// This sets `PRAGMA query_only` on the connection for the duration of the [TransactionReadonly].
+    /// Any attempt to write to the database, for example with a custom sql function, will fail.
+    /// The pragma is reset when the [TransactionReadonly] is dropped.
+    pub fn transaction_readonly<S>(&mut self, db: &Database<S>) -> TransactionReadonly<S> {
+        let conn: &Connection = self.connection(db);
+        let txn =
+            rusqlite::Transaction::new_unchecked(conn, TransactionBehavior::Deferred).unwrap();
+        let inner = Transaction::new_checked(txn, db.schema_version);
+        conn.pragma_update(None, "query_only", true).unwrap();
+        TransactionReadonly { inner, conn }
+    }
+
     /// Create a [TransactionMut].
     /// This operation needs to wait for all other [TransactionMut]s for this database to be finished.
     ///
@@ -43,11 +58,7 @@ impl LocalClient {
     /// was created. This can happen for example by running another instance of your program with
     /// additional migrations.
     pub fn transaction_mut<S>(&mut self, db: &Database<S>) -> TransactionMut<S> {
-        use r2d2::ManageConnection;
-        // TODO: could check here if the existing connection is good to use.
-        // TODO: make sure that when reusing a connection, the foreign keys are checked (migration doesn't)
-        // .pragma_update(None, "foreign_keys", "ON").unwrap();
-        let conn = self.conn.insert(db.manager.connect().unwrap());
+        let conn = self.connection(db);
         let txn = conn
             .transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)
             .unwrap();
@@ -69,6 +80,29 @@ impl LocalClient {
         }
     }
 
+    /// Get the connection of this [LocalClient], or make a new one.
+    ///
+    /// The connection is reused between transactions, so that prepared statements
+    /// are cached across transactions.
+    /// It is always for the same database, because a [crate::migration::Config] can only be opened once.
+    fn connection<S>(&mut self, db: &Database<S>) -> &mut Connection {
+        use r2d2::ManageConnection;
+        match &mut self.conn {
+            Some(conn) => {
+                // the connection could have been used for migrations, which disables foreign keys
+                // changing the pragma expires all cached statements, so it is only done when needed
+                let foreign_keys: bool = conn
+                    .pragma_query_value(None, "foreign_keys", |row| row.get(0))
+                    .unwrap();
+                if !foreign_keys {
+                    conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+                }
+            }
+            None => self.conn = Some(db.manager.connect().unwrap()),
+        }
+        self.conn.as_mut().unwrap()
+    }
+
     /// Create a [LocalClient] if it was not created yet on this thread.
     ///
     /// Async tasks often share their thread and can thus not use this method.
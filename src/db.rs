@@ -83,6 +83,16 @@ impl<T: Table> Typed for Join<T> {
 ///
 /// [TableRow] is covariant in `'t` and restricted to a single thread to prevent it from being used in a different transaction.
 ///
+/// A [TableRow] that is returned by a query is a proof that the row exists.
+/// This proof stays valid for the rest of the transaction, because rows can not be deleted
+/// using a [crate::Transaction] or [crate::TransactionMut].
+/// Only [crate::TransactionWeak] can delete rows, which is why it does not give this guarantee.
+///
+/// The lifetime `'t` is the lifetime of the transaction, which borrows the [LocalClient] mutably.
+/// This makes it impossible to start a new transaction while a [TableRow] of the old one is still in use.
+/// Together with [TableRow] not being [Send], this makes sure that a [TableRow] can not be used
+/// in a different transaction where the row might not exist anymore.
+///
 /// Note that the [TableRow] can typically only be used at the top level of each query (not inside aggregates).
 /// `rustc` sometimes suggested making the transaction lifetime `'static` to get around this issue.
 /// While it is a valid and correct suggestion, you probably don't want a `'static` transaction.
@@ -100,6 +110,56 @@ pub struct TableRowInner<T> {
     pub(crate) idx: i64,
 }
 
+impl<'t, T> TableRow<'t, T> {
+    /// Get the id of the row.
+    ///
+    /// The id is stable for as long as the row exists, so it can be used in URLs or by other systems.
+    pub fn id(&self) -> i64 {
+        self.inner.idx
+    }
+
+    /// Make a [TableRow] from an id without checking that the row exists.
+    ///
+    /// This is mostly useful in combination with [crate::TransactionWeak], where the id
+    /// is retrieved in some other way.
+    /// With the `serde` feature, `Transaction::check_row` can be used instead to check that the row exists.
+    ///
+    /// The caller is responsible for making sure that the row with this id exists in table `T`
+    /// for as long as the [TableRow] is used.
+    /// Otherwise queries that rely on the existence of the row, like [crate::Transaction::query_one],
+    /// can panic or give wrong results.
+    /// Rows can be deleted in a [crate::TransactionWeak], so a [TableRow] made there is only valid
+    /// until the row is deleted.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient, TableRow};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     User { name: String },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// let alice = txn.insert(User { name: "Alice" });
+    /// let url = format!("/user/{}", alice.id());
+    ///
+    /// let id = url.strip_prefix("/user/").unwrap().parse().unwrap();
+    /// let user = TableRow::<User>::from_id_unchecked(id);
+    /// assert_eq!(user, alice);
+    /// assert_eq!(txn.query_one(user.name()), "Alice");
+    /// ```
+    pub fn from_id_unchecked(id: i64) -> Self {
+        Self {
+            _p: PhantomData,
+            _local: PhantomData,
+            inner: TableRowInner {
+                _p: PhantomData,
+                idx: id,
+            },
+        }
+    }
+}
+
 impl<'t, T> PartialEq for TableRow<'t, T> {
     fn eq(&self, other: &Self) -> bool {
         self.inner.idx == other.inner.idx
@@ -163,6 +223,77 @@ impl<T> rusqlite::ToSql for TableRow<'_, T> {
     }
 }
 
+/// [TableRow] is serialized as its id.
+///
+/// Note that [TableRow] does not implement [serde::Deserialize], because a deserialized
+/// id does not prove that the row exists. Deserialize an [UncheckedRow] instead.
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for TableRow<'_, T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.inner.idx.serialize(serializer)
+    }
+}
+
+/// Row id that was deserialized and might not exist.
+///
+/// **An [UncheckedRow] is not a proof that the row exists.**
+/// It could come from an old serialized value, a different database or be made up completely.
+/// Use [crate::Transaction::check_row] to turn it into a [TableRow] if the row exists.
+#[cfg(feature = "serde")]
+pub struct UncheckedRow<T> {
+    pub(crate) inner: TableRowInner<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> Debug for UncheckedRow<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "db_{}", self.inner.idx)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> Clone for UncheckedRow<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+#[cfg(feature = "serde")]
+impl<T> Copy for UncheckedRow<T> {}
+
+#[cfg(feature = "serde")]
+impl<T> PartialEq for UncheckedRow<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.idx == other.inner.idx
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> From<TableRow<'_, T>> for UncheckedRow<T> {
+    fn from(value: TableRow<'_, T>) -> Self {
+        Self { inner: value.inner }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for UncheckedRow<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.inner.idx.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for UncheckedRow<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let idx = i64::deserialize(deserializer)?;
+        Ok(Self {
+            inner: TableRowInner {
+                _p: PhantomData,
+                idx,
+            },
+        })
+    }
+}
+
 #[cfg(test)]
 #[allow(unused)]
 mod tests {
@@ -2,17 +2,26 @@
 //! The layout is hashable and the hashes are independent
 //! of the column ordering and some other stuff.
 
-use std::{marker::PhantomData, ops::Deref};
+use std::{
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::Deref,
+};
 
-use sea_query::TableCreateStatement;
+use sea_query::{QueryBuilder, SqliteQueryBuilder, TableCreateStatement};
 
-use crate::value::{EqTyp, MyTyp};
+use crate::{
+    ast::MySelect,
+    value::{EqTyp, MyTyp, Typed},
+    IntoColumn,
+};
 
 #[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ColumnType {
     Integer = 0,
     Float = 1,
     String = 2,
+    Blob = 3,
 }
 
 impl ColumnType {
@@ -22,16 +31,45 @@ impl ColumnType {
             ColumnType::Integer => T::Integer,
             ColumnType::Float => T::custom("REAL"),
             ColumnType::String => T::Text,
+            ColumnType::Blob => T::Blob,
         }
     }
 }
 
-#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Column {
     pub name: String,
     pub typ: ColumnType,
     pub nullable: bool,
     pub fk: Option<(String, String)>,
+    pub check: Option<String>,
+    pub default: Option<String>,
+    pub generated: Option<Generated>,
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Generated {
+    pub expr: String,
+    pub stored: bool,
+}
+
+impl Hash for Column {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.typ.hash(state);
+        self.nullable.hash(state);
+        self.fk.hash(state);
+        // only hash these if they exist, this keeps the hash of existing schemas the same
+        if let Some(check) = &self.check {
+            check.hash(state);
+        }
+        if let Some(default) = &self.default {
+            default.hash(state);
+        }
+        if let Some(generated) = &self.generated {
+            generated.hash(state);
+        }
+    }
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Default)]
@@ -86,6 +124,15 @@ impl Table {
             } else {
                 def.not_null();
             }
+            if let Some(check) = &col.check {
+                def.check(Expr::cust(check));
+            }
+            if let Some(default) = &col.default {
+                def.default(Expr::cust(format!("({default})")));
+            }
+            if let Some(generated) = &col.generated {
+                def.generated(Expr::cust(&generated.expr), generated.stored);
+            }
             create.col(&mut def);
             if let Some((table, fk)) = &col.fk {
                 create.foreign_key(
@@ -111,6 +158,101 @@ pub struct Schema {
     pub tables: MyVec<(String, Table)>,
 }
 
+/// A single difference between two schemas, as returned by [crate::migration::Migrator::plan].
+///
+/// Columns and unique constraints are compared by name.
+/// A column that exists in both schemas but has a different type, nullability,
+/// foreign key, check, default or generated expression is reported as [SchemaChange::ChangeColumn].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaChange {
+    CreateTable { table: String },
+    DropTable { table: String },
+    AddColumn { table: String, column: String },
+    DropColumn { table: String, column: String },
+    ChangeColumn { table: String, column: String },
+    AddUnique { table: String, columns: Vec<String> },
+    DropUnique { table: String, columns: Vec<String> },
+}
+
+impl std::fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaChange::CreateTable { table } => write!(f, "create table `{table}`"),
+            SchemaChange::DropTable { table } => write!(f, "drop table `{table}`"),
+            SchemaChange::AddColumn { table, column } => {
+                write!(f, "add column `{column}` to table `{table}`")
+            }
+            SchemaChange::DropColumn { table, column } => {
+                write!(f, "drop column `{column}` from table `{table}`")
+            }
+            SchemaChange::ChangeColumn { table, column } => {
+                write!(f, "change column `{column}` of table `{table}`")
+            }
+            SchemaChange::AddUnique { table, columns } => {
+                write!(f, "add unique ({}) to table `{table}`", columns.join(", "))
+            }
+            SchemaChange::DropUnique { table, columns } => {
+                write!(
+                    f,
+                    "drop unique ({}) from table `{table}`",
+                    columns.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl Schema {
+    /// List the changes that are needed to go from `self` to `new`.
+    pub fn diff(&self, new: &Schema) -> Vec<SchemaChange> {
+        let mut changes = vec![];
+        for (name, table) in &*self.tables {
+            let table_name = name.clone();
+            let Some((_, new_table)) = new.tables.iter().find(|(n, _)| n == name) else {
+                changes.push(SchemaChange::DropTable { table: table_name });
+                continue;
+            };
+            for col in &*table.columns {
+                let column = col.name.clone();
+                let table = table_name.clone();
+                match new_table.columns.iter().find(|c| c.name == col.name) {
+                    None => changes.push(SchemaChange::DropColumn { table, column }),
+                    Some(new_col) if new_col != col => {
+                        changes.push(SchemaChange::ChangeColumn { table, column })
+                    }
+                    Some(_) => {}
+                }
+            }
+            for col in &*new_table.columns {
+                if !table.columns.iter().any(|c| c.name == col.name) {
+                    let (table, column) = (table_name.clone(), col.name.clone());
+                    changes.push(SchemaChange::AddColumn { table, column });
+                }
+            }
+            for unique in &*table.uniques {
+                if !new_table.uniques.contains(unique) {
+                    let (table, columns) = (table_name.clone(), unique.columns.to_vec());
+                    changes.push(SchemaChange::DropUnique { table, columns });
+                }
+            }
+            for unique in &*new_table.uniques {
+                if !table.uniques.contains(unique) {
+                    let (table, columns) = (table_name.clone(), unique.columns.to_vec());
+                    changes.push(SchemaChange::AddUnique { table, columns });
+                }
+            }
+        }
+        for (name, _) in &*new.tables {
+            if !self.tables.iter().any(|(n, _)| n == name) {
+                changes.push(SchemaChange::CreateTable {
+                    table: name.clone(),
+                });
+            }
+        }
+        changes
+    }
+}
+
 #[cfg(feature = "dev")]
 pub mod dev {
     use std::{
@@ -155,6 +297,19 @@ pub mod dev {
         b.ast.hash(&mut hasher);
         format!("{:x}", hasher.finish())
     }
+
+    /// List the changes between schema `From` and schema `To`.
+    ///
+    /// Unlike [hash_schema], this shows which tables, columns and unique constraints changed.
+    /// Tables are compared by name, so a renamed table shows up as a dropped and created table.
+    pub fn diff_schema<From: crate::migrate::Schema, To: crate::migrate::Schema>(
+    ) -> Vec<super::SchemaChange> {
+        let mut from = crate::migrate::TableTypBuilder::default();
+        From::typs(&mut from);
+        let mut to = crate::migrate::TableTypBuilder::default();
+        To::typs(&mut to);
+        from.ast.diff(&to.ast)
+    }
 }
 
 pub struct TypBuilder<S> {
@@ -173,16 +328,66 @@ impl<S> Default for TypBuilder<S> {
 
 impl<S> TypBuilder<S> {
     pub fn col<T: SchemaType<S>>(&mut self, name: &'static str) {
+        let item = Self::column::<T>(name);
+        self.ast.columns.insert(item)
+    }
+
+    /// Same as [TypBuilder::col], but the column also has a default value.
+    pub fn col_default<'x, T: SchemaType<S>>(
+        &mut self,
+        name: &'static str,
+        default: impl IntoColumn<'x, S, Typ = T>,
+    ) {
+        let ast = MySelect::default();
+        let expr = default.into_column().inner.build_expr(ast.builder());
+        let mut sql = String::new();
+        SqliteQueryBuilder.prepare_simple_expr(&expr, &mut sql);
+
+        let mut item = Self::column::<T>(name);
+        item.default = Some(sql);
+        self.ast.columns.insert(item)
+    }
+
+    /// Same as [TypBuilder::col], but the column is generated from the sql expression `expr`.
+    pub fn col_generated<T: SchemaType<S>>(
+        &mut self,
+        name: &'static str,
+        expr: &'static str,
+        stored: bool,
+    ) {
+        let mut item = Self::column::<T>(name);
+        item.generated = Some(Generated {
+            expr: expr.to_owned(),
+            stored,
+        });
+        self.ast.columns.insert(item)
+    }
+
+    /// Add a check constraint to the column `name` that the blob has exactly `len` bytes.
+    pub fn blob_len<T: SchemaType<S> + MyTyp<Sql = Vec<u8>>>(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) {
+        let item = self.ast.columns.inner.iter_mut().find(|x| x.name == name);
+        let item = item.expect("column should be defined before adding the check");
+        item.check = Some(format!("length(\"{name}\") = {len}"));
+    }
+
+    fn column<T: SchemaType<S>>(name: &'static str) -> Column {
         let mut item = Column {
             name: name.to_owned(),
             typ: T::TYP,
             nullable: T::NULLABLE,
             fk: None,
+            check: T::CHECK.map(|check| format!("\"{name}\" {check}")),
+            default: None,
+            generated: None,
         };
         if let Some((table, fk)) = T::FK {
             item.fk = Some((table.to_owned(), fk.to_owned()))
         }
-        self.ast.columns.insert(item)
+        item
     }
 
     pub fn unique(&mut self, cols: &[&'static str]) {
@@ -200,13 +405,15 @@ struct Null;
 struct NotNull;
 
 // TODO: maybe remove this trait?
-// currently this prevents storing booleans and nested `Option`.
+// currently this prevents storing nested `Option`.
 #[diagnostic::on_unimplemented(
     message = "Can not use `{Self}` as a column type in schema `{S}`",
     note = "Table names can be used as schema column types as long as they are not #[no_reference]"
 )]
 trait SchemaType<S>: MyTyp {
     type N;
+    /// Check constraint that is added after the column name.
+    const CHECK: Option<&'static str> = None;
 }
 
 impl<S> SchemaType<S> for String {
@@ -218,8 +425,35 @@ impl<S> SchemaType<S> for i64 {
 impl<S> SchemaType<S> for f64 {
     type N = NotNull;
 }
+impl<S> SchemaType<S> for Vec<u8> {
+    type N = NotNull;
+}
+impl<S> SchemaType<S> for bool {
+    type N = NotNull;
+    const CHECK: Option<&'static str> = Some("IN (0, 1)");
+}
 impl<S, T: SchemaType<S, N = NotNull>> SchemaType<S> for Option<T> {
     type N = Null;
+    const CHECK: Option<&'static str> = T::CHECK;
+}
+#[cfg(feature = "uuid")]
+impl<S> SchemaType<S> for uuid::Uuid {
+    type N = NotNull;
+}
+#[cfg(feature = "chrono")]
+impl<S> SchemaType<S> for chrono::NaiveDateTime {
+    type N = NotNull;
+}
+#[cfg(feature = "chrono")]
+impl<S> SchemaType<S> for chrono::DateTime<chrono::Utc> {
+    type N = NotNull;
+}
+#[cfg(feature = "json")]
+impl<S, T> SchemaType<S> for crate::value::json::Json<T>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + 'static,
+{
+    type N = NotNull;
 }
 // only tables with `Referer = ()` are valid columns
 #[diagnostic::do_not_recommend]
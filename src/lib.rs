@@ -20,25 +20,38 @@ mod transaction;
 mod value;
 mod writable;
 
-pub use aggregate::aggregate;
+pub use aggregate::{aggregate, exists, not_exists};
 pub use client::LocalClient;
 pub use db::TableRow;
+#[cfg(feature = "serde")]
+pub use db::UncheckedRow;
 pub use dummy_impl::Dummy;
 pub use dummy_impl::IntoDummy;
 use hash::TypBuilder;
 use ref_cast::RefCast;
 pub use rows::Rows;
 pub use rust_query_macros::Dummy;
-pub use transaction::{Database, Transaction, TransactionMut, TransactionWeak};
-pub use value::{optional::optional, Column, IntoColumn, UnixEpoch};
+pub use transaction::{
+    Database, Transaction, TransactionMut, TransactionReadonly, TransactionWeak, UpdateConflict,
+};
+#[cfg(feature = "chrono")]
+pub use value::datetime::UtcNow;
+#[cfg(feature = "json")]
+pub use value::json::Json;
+pub use value::{
+    call_fn, concat_ws, if_then, optional::optional, raw_sql, window::window, Column, IntoColumn,
+    NowString, UnixEpoch,
+};
 
 /// Types that are used as closure arguments.
 ///
 /// You generally don't need to import these types.
 pub mod args {
-    pub use crate::aggregate::Aggregate;
+    pub use crate::aggregate::{Aggregate, Exists};
     pub use crate::query::Query;
     pub use crate::value::optional::Optional;
+    pub use crate::value::window::Window;
+    pub use crate::value::FnArgs;
 }
 
 /// Types to declare schemas and migrations.
@@ -46,7 +59,8 @@ pub mod args {
 /// A good starting point is too look at [crate::migration::schema].
 pub mod migration {
     #[cfg(feature = "dev")]
-    pub use crate::hash::dev::hash_schema;
+    pub use crate::hash::dev::{diff_schema, hash_schema};
+    pub use crate::hash::SchemaChange;
     pub use crate::migrate::{Alter, Config, Create, Migrator, NoTable};
     pub use rust_query_macros::schema;
 }
@@ -70,7 +84,7 @@ pub mod private {
     };
     pub use crate::query::show_sql;
     pub use crate::value::{into_owned, new_column, MyTyp, Typed, ValueBuilder};
-    pub use crate::writable::{Reader, Writable};
+    pub use crate::writable::{ColumnOrDefault, Reader, Writable};
 
     pub use ref_cast::RefCast;
     pub use sea_query::SimpleExpr;
@@ -1,9 +1,20 @@
-use std::{marker::PhantomData, path::Path, sync::atomic::AtomicBool};
+use std::{
+    marker::PhantomData,
+    panic::RefUnwindSafe,
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Arc, OnceLock},
+    time::Duration,
+};
 
-use rusqlite::{config::DbConfig, Connection};
+use rusqlite::{
+    config::DbConfig,
+    functions::{Context, FunctionFlags},
+    types::ToSql,
+    Connection,
+};
 use sea_query::{
-    Alias, ColumnDef, InsertStatement, IntoTableRef, SqliteQueryBuilder, TableDropStatement,
-    TableRenameStatement,
+    Alias, ColumnDef, InsertStatement, IntoTableRef, SqliteQueryBuilder, TableAlterStatement,
+    TableDropStatement, TableRenameStatement,
 };
 use sea_query_rusqlite::RusqliteBinder;
 
@@ -12,7 +23,8 @@ use crate::{
     ast::MySelect,
     client::LocalClient,
     dummy_impl::{Cacher, DummyImpl, Prepared, Row},
-    hash,
+    hash::{self, SchemaChange},
+    query::{has_math_functions, TableScanHook, HAS_MATH_FUNCTIONS, TABLE_SCAN},
     schema_pragma::read_schema,
     transaction::Database,
     value::{self, DynTypedExpr, Private},
@@ -185,10 +197,14 @@ pub struct SchemaBuilder<'x, 'a> {
     scope: Scope,
     conn: &'x rusqlite::Transaction<'x>,
     drop: Vec<TableDropStatement>,
+    drop_column: Vec<TableAlterStatement>,
     rename: Vec<TableRenameStatement>,
+    progress: Option<(u64, &'x mut ProgressHook<'x>)>,
     _p: PhantomData<fn(&'a ()) -> &'a ()>,
 }
 
+type ProgressHook<'t> = dyn 't + FnMut(&str, u64);
+
 impl<'a> SchemaBuilder<'_, 'a> {
     pub fn migrate_table<From: Table, To: Table>(&mut self, m: M<'a, From, To>) {
         self.create_inner::<From::Schema, To>(|rows| {
@@ -246,6 +262,7 @@ impl<'a> SchemaBuilder<'_, 'a> {
         let mut statement = self.conn.prepare(&sql).unwrap();
         let mut rows = statement.query(&*values.as_params()).unwrap();
 
+        let mut count = 0;
         while let Some(row) = rows.next().unwrap() {
             let row = Row {
                 row,
@@ -271,6 +288,19 @@ impl<'a> SchemaBuilder<'_, 'a> {
             let (sql, values) = insert.build_rusqlite(SqliteQueryBuilder);
             let mut statement = self.conn.prepare_cached(&sql).unwrap();
             statement.execute(&*values.as_params()).unwrap();
+
+            count += 1;
+            if let Some((every, progress)) = &mut self.progress {
+                if count % *every == 0 {
+                    progress(To::NAME, count);
+                }
+            }
+        }
+        // the final count is only reported if it was not reported already
+        if let Some((every, progress)) = &mut self.progress {
+            if count == 0 || count % *every != 0 {
+                progress(To::NAME, count);
+            }
         }
     }
 
@@ -279,6 +309,14 @@ impl<'a> SchemaBuilder<'_, 'a> {
         let step = sea_query::Table::drop().table(name).take();
         self.drop.push(step);
     }
+
+    pub fn drop_column<T: Table>(&mut self, name: &'static str) {
+        let step = sea_query::Table::alter()
+            .table(Alias::new(T::NAME))
+            .drop_column(Alias::new(name))
+            .take();
+        self.drop_column.push(step);
+    }
 }
 
 fn new_table<T: Table>(conn: &Connection, alias: TmpTable) {
@@ -311,10 +349,29 @@ pub trait Migration<'a> {
 pub struct Config {
     manager: r2d2_sqlite::SqliteConnectionManager,
     init: Box<dyn FnOnce(&rusqlite::Transaction)>,
+    busy_timeout: Option<Duration>,
+    attached: Vec<(PathBuf, String)>,
+    functions: Vec<ScalarFunction>,
+    table_scan: Option<TableScanHook>,
+    trace: Option<TraceHook>,
 }
 
 static ALLOWED: AtomicBool = AtomicBool::new(true);
 
+type ScalarFunction = Arc<dyn Fn(&Connection) -> rusqlite::Result<()> + Send + Sync>;
+
+type TraceHook = Box<dyn Fn(&str, Duration) + Send + Sync>;
+
+/// Set by [Config::trace].
+/// See [crate::query::TABLE_SCAN] for why this can be global.
+static TRACE: OnceLock<TraceHook> = OnceLock::new();
+
+fn trace_statement(sql: &str, duration: Duration) {
+    if let Some(trace) = TRACE.get() {
+        trace(sql, duration)
+    }
+}
+
 impl Config {
     /// Open a database that is stored in a file.
     /// Creates the database if it does not exist.
@@ -335,22 +392,121 @@ impl Config {
 
     fn open_internal(manager: r2d2_sqlite::SqliteConnectionManager) -> Self {
         assert!(ALLOWED.swap(false, std::sync::atomic::Ordering::Relaxed));
-        let manager = manager.with_init(|inner| {
-            inner.pragma_update(None, "journal_mode", "WAL")?;
-            inner.pragma_update(None, "synchronous", "NORMAL")?;
-            inner.pragma_update(None, "foreign_keys", "ON")?;
-            inner.set_db_config(DbConfig::SQLITE_DBCONFIG_DQS_DDL, false)?;
-            inner.set_db_config(DbConfig::SQLITE_DBCONFIG_DQS_DML, false)?;
-            inner.set_db_config(DbConfig::SQLITE_DBCONFIG_DEFENSIVE, true)?;
-            Ok(())
-        });
-
         Self {
             manager,
             init: Box::new(|_| {}),
+            busy_timeout: None,
+            attached: vec![],
+            functions: vec![],
+            table_scan: None,
+            trace: None,
         }
     }
 
+    /// Set how long to wait for a lock on the database before giving up with a busy error.
+    ///
+    /// This is applied to every connection using `PRAGMA busy_timeout`.
+    /// Other connections to the same database, for example from [crate::TransactionWeak::unchecked_transaction]
+    /// or from other processes, can hold a lock that is not managed by [crate::LocalClient].
+    /// By default the busy timeout of rusqlite is used, which is 5 seconds.
+    pub fn busy_timeout(mut self, timeout: Duration) -> Self {
+        self.busy_timeout = Some(timeout);
+        self
+    }
+
+    /// Attach another database file as the schema `name` on every connection.
+    ///
+    /// The tables of the attached database are not part of the [Schema] and are not migrated.
+    /// They can be read with [crate::raw_sql] by qualifying the table with `name`.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, raw_sql, Database, LocalClient};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     User { country: String },
+    /// # }
+    /// # use v0::*;
+    /// # let dir = std::env::temp_dir().join(format!("rust-query-attach-doc-{}", std::process::id()));
+    /// # std::fs::create_dir_all(&dir).unwrap();
+    /// # let path = dir.join("reference.sqlite");
+    /// # rusqlite::Connection::open(&path).unwrap().execute_batch(
+    /// #     "CREATE TABLE country (code TEXT, name TEXT);
+    /// #     INSERT INTO country VALUES ('NL', 'Netherlands');"
+    /// # ).unwrap();
+    /// let config = Config::open_in_memory().attach(path, "reference");
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(config).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// let user = txn.insert(User { country: "NL" });
+    /// let country = txn.query_one(raw_sql::<Option<String>, _>(
+    ///     "(SELECT name FROM reference.country WHERE code = ?)",
+    ///     (user.country(),),
+    /// ));
+    /// assert_eq!(country.as_deref(), Some("Netherlands"));
+    /// # std::fs::remove_dir_all(dir).unwrap();
+    /// ```
+    pub fn attach(mut self, path: impl AsRef<Path>, name: &str) -> Self {
+        self.attached
+            .push((path.as_ref().to_owned(), name.to_owned()));
+        self
+    }
+
+    /// Call `warn` for every query that reads a full table, because there is no index to search.
+    ///
+    /// Before executing a query, `EXPLAIN QUERY PLAN` is used to check if sqlite
+    /// will `SCAN` a table. The arguments of `warn` are the sql of the query and the
+    /// line of the query plan with the `SCAN`.
+    /// This is meant to find missing indexes during development, it is off by default
+    /// because it doubles the number of queries.
+    /// Only queries are checked, inserts and updates are not.
+    /// ```
+    /// # use rust_query::migration::Config;
+    /// let config = Config::open_in_memory().warn_on_table_scan(|sql, detail| {
+    ///     eprintln!("warning: {detail} in `{sql}`");
+    /// });
+    /// ```
+    pub fn warn_on_table_scan(mut self, warn: impl Fn(&str, &str) + Send + Sync + 'static) -> Self {
+        self.table_scan = Some(Box::new(warn));
+        self
+    }
+
+    /// Call `trace` with the sql and the duration of every statement that is executed.
+    ///
+    /// This includes queries, inserts, updates and deletes, but also the statements that are
+    /// used for migrations. The sql contains `?` for the parameters, the values are not included.
+    /// The duration is measured by sqlite and `trace` is called when the statement is finished.
+    /// ```
+    /// # use rust_query::migration::Config;
+    /// let config = Config::open_in_memory().trace(|sql, duration| {
+    ///     eprintln!("{duration:?}: {sql}");
+    /// });
+    /// ```
+    pub fn trace(mut self, trace: impl Fn(&str, Duration) + Send + Sync + 'static) -> Self {
+        self.trace = Some(Box::new(trace));
+        self
+    }
+
+    /// Register a custom scalar sql function that can be called with [crate::call_fn].
+    ///
+    /// The function is registered on every connection and receives `n_args` arguments.
+    /// Use `-1` for `n_args` to allow any number of arguments.
+    ///
+    /// The function must be pure, it should always return the same result for the same arguments.
+    /// It is registered as deterministic, which allows sqlite to use it in indexes and to optimize queries.
+    pub fn scalar_function<T: ToSql>(
+        mut self,
+        name: &'static str,
+        n_args: i32,
+        f: impl Fn(&Context) -> rusqlite::Result<T> + Send + Sync + RefUnwindSafe + 'static,
+    ) -> Self {
+        let f = Arc::new(f);
+        self.functions.push(Arc::new(move |conn| {
+            let flags = FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+            let f = f.clone();
+            conn.create_scalar_function(name, n_args, flags, move |ctx| f(ctx))
+        }));
+        self
+    }
+
     /// Execute a raw sql statement if the database was just created.
     ///
     /// The statement is executed after creating the empty database and executingall previous statements.
@@ -373,7 +529,39 @@ impl LocalClient {
     /// This function will panic if the schema on disk does not match what is expected for its `user_version`.
     pub fn migrator<'t, S: Schema>(&'t mut self, config: Config) -> Option<Migrator<'t, S>> {
         use r2d2::ManageConnection;
-        let conn = self.conn.insert(config.manager.connect().unwrap());
+        let busy_timeout = config.busy_timeout;
+        let attached = config.attached;
+        let functions = config.functions;
+        let trace = config.trace.is_some();
+        if let Some(hook) = config.trace {
+            // this can only fail if the hook was already set, but there is only one `Config`
+            let _ = TRACE.set(hook);
+        }
+        let manager = config.manager.with_init(move |inner| {
+            inner.pragma_update(None, "journal_mode", "WAL")?;
+            inner.pragma_update(None, "synchronous", "NORMAL")?;
+            inner.pragma_update(None, "foreign_keys", "ON")?;
+            inner.set_db_config(DbConfig::SQLITE_DBCONFIG_DQS_DDL, false)?;
+            inner.set_db_config(DbConfig::SQLITE_DBCONFIG_DQS_DML, false)?;
+            inner.set_db_config(DbConfig::SQLITE_DBCONFIG_DEFENSIVE, true)?;
+            if let Some(timeout) = busy_timeout {
+                inner.pragma_update(None, "busy_timeout", timeout.as_millis() as i64)?;
+            }
+            for (path, name) in &attached {
+                let path = path.to_string_lossy();
+                inner.execute("ATTACH ?1 AS ?2", (path.as_ref(), name))?;
+            }
+            for function in &functions {
+                function(inner)?;
+            }
+            if trace {
+                inner.profile(Some(trace_statement));
+            }
+            Ok(())
+        });
+        let conn = self.conn.insert(manager.connect().unwrap());
+        // this can only fail if it was already set, but there is only one `Config`
+        let _ = HAS_MATH_FUNCTIONS.set(has_math_functions(conn));
         conn.pragma_update(None, "foreign_keys", "OFF").unwrap();
 
         let conn = conn
@@ -401,8 +589,10 @@ impl LocalClient {
         }
 
         Some(Migrator {
-            manager: config.manager,
+            manager,
             transaction: conn,
+            table_scan: config.table_scan,
+            progress: None,
             _p: PhantomData,
             _local: PhantomData,
         })
@@ -416,6 +606,8 @@ impl LocalClient {
 pub struct Migrator<'t, S> {
     manager: r2d2_sqlite::SqliteConnectionManager,
     transaction: rusqlite::Transaction<'t>,
+    table_scan: Option<TableScanHook>,
+    progress: Option<(u64, Box<ProgressHook<'t>>)>,
     _p: PhantomData<S>,
     // We want to make sure that Migrator is always used with the same LocalClient
     // so we make it local to the current thread.
@@ -424,6 +616,65 @@ pub struct Migrator<'t, S> {
 }
 
 impl<'t, S: Schema> Migrator<'t, S> {
+    /// Call `progress` every `every` rows while tables are migrated.
+    ///
+    /// The arguments of `progress` are the name of the table and the number of rows
+    /// that were migrated for that table so far.
+    /// When a table is done, `progress` is called with the total number of rows of that table,
+    /// unless that was already the last call.
+    /// Migrations are done in a single exclusive transaction, so this can be used
+    /// to show that a long running migration is still making progress.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, LocalClient};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     User { name: String },
+    /// # }
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// let m = client
+    ///     .migrator::<v0::Schema>(Config::open_in_memory())
+    ///     .unwrap()
+    ///     .progress(10_000, |table, rows| eprintln!("migrated {rows} rows of {table}"));
+    /// ```
+    pub fn progress(mut self, every: u64, progress: impl 't + FnMut(&str, u64)) -> Self {
+        assert!(every != 0, "progress must be called every one or more rows");
+        self.progress = Some((every, Box::new(progress)));
+        self
+    }
+
+    /// List the schema changes that are needed to migrate the database to schema `N`.
+    ///
+    /// The schema of the database is read and compared with `N`, nothing is changed.
+    /// This can be used to check a migration before it is applied.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config, SchemaChange}, LocalClient};
+    /// # #[schema]
+    /// # #[version(0..=1)]
+    /// # enum Schema {
+    /// #     User {
+    /// #         name: String,
+    /// #         #[version(1..)]
+    /// #         email: String,
+    /// #     },
+    /// # }
+    /// # fn main() {
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// let m = client.migrator::<v0::Schema>(Config::open_in_memory()).unwrap();
+    /// let changes = m.plan::<v1::Schema>();
+    /// assert_eq!(
+    ///     changes,
+    ///     [SchemaChange::AddColumn { table: "user".to_owned(), column: "email".to_owned() }]
+    /// );
+    /// assert_eq!(changes[0].to_string(), "add column `email` to table `user`");
+    /// # }
+    /// ```
+    pub fn plan<N: Schema>(&self) -> Vec<SchemaChange> {
+        let mut b = TableTypBuilder::default();
+        N::typs(&mut b);
+        let current = read_schema(crate::Transaction::ref_cast(&self.transaction));
+        current.diff(&b.ast)
+    }
+
     /// Apply a database migration if the current schema is `S` and return a [Migrator] for the next schema `N`.
     ///
     /// This function will panic if the schema on disk does not match what is expected for its `user_version`.
@@ -432,16 +683,26 @@ impl<'t, S: Schema> Migrator<'t, S> {
         M: Migration<'t, From = S, To = N>,
     {
         let conn = &self.transaction;
+        let mut progress = self.progress;
 
         if user_version(conn).unwrap() == S::VERSION {
             let mut builder = SchemaBuilder {
                 scope: Default::default(),
                 conn,
                 drop: vec![],
+                drop_column: vec![],
                 rename: vec![],
+                progress: progress
+                    .as_mut()
+                    .map(|(every, f)| (*every, &mut **f as &mut ProgressHook)),
                 _p: PhantomData,
             };
             m.tables(&mut builder);
+            // columns are dropped after all new tables are filled, because they might use the columns
+            for alter in builder.drop_column {
+                let sql = alter.to_string(SqliteQueryBuilder);
+                conn.execute(&sql, []).unwrap();
+            }
             for drop in builder.drop {
                 let sql = drop.to_string(SqliteQueryBuilder);
                 conn.execute(&sql, []).unwrap();
@@ -457,6 +718,8 @@ impl<'t, S: Schema> Migrator<'t, S> {
         Migrator {
             manager: self.manager,
             transaction: self.transaction,
+            table_scan: self.table_scan,
+            progress,
             _p: PhantomData,
             _local: PhantomData,
         }
@@ -474,6 +737,12 @@ impl<'t, S: Schema> Migrator<'t, S> {
         let schema_version = schema_version(conn);
         self.transaction.commit().unwrap();
 
+        // queries during migrations are not checked
+        if let Some(warn) = self.table_scan {
+            // this can only fail if the hook was already set, but there is only one `Config`
+            let _ = TABLE_SCAN.set(warn);
+        }
+
         Some(Database {
             manager: self.manager,
             schema_version,
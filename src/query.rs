@@ -2,14 +2,20 @@ use std::{
     cell::Cell,
     marker::PhantomData,
     ops::{Deref, DerefMut},
+    sync::OnceLock,
 };
 
-use sea_query::SqliteQueryBuilder;
-use sea_query_rusqlite::RusqliteBinder;
+use rusqlite::{types::ToSqlOutput, ToSql};
+use sea_query::{NullOrdering, Order, SelectStatement, SimpleExpr, SqliteQueryBuilder, UnionType};
+use sea_query_rusqlite::{RusqliteBinder, RusqliteValues};
 
 use crate::{
-    dummy_impl::{Cacher, DummyImpl, IntoDummy, Prepared, Row},
+    alias::Field,
+    ast::MySelect,
+    dummy_impl::{Cacher, Dummy, DummyImpl, IntoDummy, Prepared, Row},
     rows::Rows,
+    value::Typed,
+    IntoColumn,
 };
 
 /// This is the top level query type and dereferences to [Rows].
@@ -18,6 +24,10 @@ pub struct Query<'outer, 'inner, S> {
     pub(crate) phantom: PhantomData<&'inner &'outer ()>,
     pub(crate) q: Rows<'inner, S>,
     pub(crate) conn: &'inner rusqlite::Connection,
+    pub(crate) limit: Option<u64>,
+    pub(crate) offset: Option<u64>,
+    pub(crate) distinct: bool,
+    pub(crate) order_by: Vec<(SimpleExpr, Order, Option<NullOrdering>)>,
 }
 
 impl<'outer, 'inner, S> Deref for Query<'outer, 'inner, S> {
@@ -35,6 +45,238 @@ impl<'outer, 'inner, S> DerefMut for Query<'outer, 'inner, S> {
 }
 
 impl<'outer, 'inner, S> Query<'outer, 'inner, S> {
+    /// Return at most `n` results from the query.
+    ///
+    /// This uses the sqlite `LIMIT` clause and applies to all the `into_vec*` methods.
+    /// Results are sorted before the limit is applied, so the returned results are deterministic.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Customer { name: String },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// for name in ["Alice", "Bob", "Carol", "Dave"] {
+    ///     txn.insert(Customer { name });
+    /// }
+    /// let page = txn.query(|rows| {
+    ///     let customer = Customer::join(rows);
+    ///     rows.limit(2);
+    ///     rows.offset(1);
+    ///     rows.into_vec(customer.name())
+    /// });
+    /// assert_eq!(page, ["Bob", "Carol"]);
+    ///
+    /// let last = txn.query(|rows| {
+    ///     let customer = Customer::join(rows);
+    ///     rows.offset(3);
+    ///     rows.into_vec(customer.name())
+    /// });
+    /// assert_eq!(last, ["Dave"]);
+    /// ```
+    pub fn limit(&mut self, n: u64) {
+        self.limit = Some(n);
+    }
+
+    /// Skip the first `n` results of the query.
+    ///
+    /// This uses the sqlite `OFFSET` clause and applies to all the `into_vec*` methods.
+    /// See [Query::limit] for an example.
+    pub fn offset(&mut self, n: u64) {
+        self.offset = Some(n);
+    }
+
+    /// Remove duplicate results from [Query::into_vec].
+    ///
+    /// This uses `SELECT DISTINCT`, so two results are the same if all the columns in the
+    /// dummy passed to [Query::into_vec] are the same.
+    /// Results are still sorted by all these columns, and [Query::limit] and [Query::offset]
+    /// apply after duplicates are removed.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Customer { name: String, city: String, state: String },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// # txn.insert(Customer { name: "Alice", city: "Delft", state: "ZH" });
+    /// # txn.insert(Customer { name: "Bob", city: "Delft", state: "ZH" });
+    /// # txn.insert(Customer { name: "Carol", city: "Leiden", state: "ZH" });
+    /// let places = txn.query(|rows| {
+    ///     let customer = Customer::join(rows);
+    ///     rows.distinct();
+    ///     rows.into_vec((customer.city(), customer.state()))
+    /// });
+    /// assert_eq!(places.len(), 2);
+    /// ```
+    pub fn distinct(&mut self) {
+        self.distinct = true;
+    }
+
+    /// Sort the results of the query by `key` in ascending order.
+    ///
+    /// This applies to [Query::into_vec] and [Query::for_each].
+    /// When this is called multiple times, the first key is the most important.
+    /// The results are still sorted by all the returned columns after these keys,
+    /// so the order of the results is always deterministic.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Customer { name: String, score: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// # txn.insert(Customer { name: "Alice", score: 3 });
+    /// # txn.insert(Customer { name: "Bob", score: 5 });
+    /// # txn.insert(Customer { name: "Carol", score: 3 });
+    /// let ranking = txn.query(|rows| {
+    ///     let customer = Customer::join(rows);
+    ///     rows.order_by_desc(customer.score());
+    ///     rows.into_vec(customer.name())
+    /// });
+    /// assert_eq!(ranking, ["Bob", "Alice", "Carol"]);
+    /// ```
+    pub fn order_by_asc(&mut self, key: impl IntoColumn<'inner, S>) {
+        self.order_by_private(key, Order::Asc, None)
+    }
+
+    /// Sort the results of the query by `key` in descending order.
+    ///
+    /// See [Query::order_by_asc] for more information.
+    pub fn order_by_desc(&mut self, key: impl IntoColumn<'inner, S>) {
+        self.order_by_private(key, Order::Desc, None)
+    }
+
+    /// Sort the results of the query by `key` in ascending order, using the collation `collation`.
+    ///
+    /// The built-in collations of sqlite are `BINARY` (the default), `NOCASE` and `RTRIM`.
+    /// `NOCASE` only ignores the case of ASCII characters.
+    /// The collation only applies to the ordering, not to the returned values.
+    /// This is the same as using [Query::order_by_asc] with [crate::Column::collate].
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Customer { name: String },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// for name in ["bob", "Carol", "alice"] {
+    ///     txn.insert(Customer { name });
+    /// }
+    /// let names = txn.query(|rows| {
+    ///     let customer = Customer::join(rows);
+    ///     rows.order_by_asc_collate(customer.name(), "NOCASE");
+    ///     rows.into_vec(customer.name())
+    /// });
+    /// assert_eq!(names, ["alice", "bob", "Carol"]);
+    /// ```
+    pub fn order_by_asc_collate(
+        &mut self,
+        key: impl IntoColumn<'inner, S, Typ = String>,
+        collation: &str,
+    ) {
+        self.order_by_private(key.into_column().collate(collation), Order::Asc, None)
+    }
+
+    /// Sort the results of the query by `key` in descending order, using the collation `collation`.
+    ///
+    /// See [Query::order_by_asc_collate] for more information.
+    pub fn order_by_desc_collate(
+        &mut self,
+        key: impl IntoColumn<'inner, S, Typ = String>,
+        collation: &str,
+    ) {
+        self.order_by_private(key.into_column().collate(collation), Order::Desc, None)
+    }
+
+    /// Sort the results of the query by the optional `key` in ascending order, with [None] values first.
+    ///
+    /// Sqlite considers `NULL` to be smaller than any other value, so this is the same as [Query::order_by_asc].
+    /// It can still be useful to make the intention explicit.
+    /// See [Query::order_by_desc_nulls_last] for an example.
+    pub fn order_by_asc_nulls_first<T>(
+        &mut self,
+        key: impl IntoColumn<'inner, S, Typ = Option<T>>,
+    ) {
+        self.order_by_private(key, Order::Asc, Some(NullOrdering::First))
+    }
+
+    /// Sort the results of the query by the optional `key` in ascending order, with [None] values last.
+    ///
+    /// See [Query::order_by_desc_nulls_last] for an example.
+    pub fn order_by_asc_nulls_last<T>(&mut self, key: impl IntoColumn<'inner, S, Typ = Option<T>>) {
+        self.order_by_private(key, Order::Asc, Some(NullOrdering::Last))
+    }
+
+    /// Sort the results of the query by the optional `key` in descending order, with [None] values first.
+    ///
+    /// See [Query::order_by_desc_nulls_last] for an example.
+    pub fn order_by_desc_nulls_first<T>(
+        &mut self,
+        key: impl IntoColumn<'inner, S, Typ = Option<T>>,
+    ) {
+        self.order_by_private(key, Order::Desc, Some(NullOrdering::First))
+    }
+
+    /// Sort the results of the query by the optional `key` in descending order, with [None] values last.
+    ///
+    /// Sqlite considers `NULL` to be smaller than any other value, so this is the same as [Query::order_by_desc].
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Player { name: String, score: Option<i64> },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// txn.insert(Player { name: "Alice", score: Some(3) });
+    /// txn.insert(Player { name: "Bob", score: None::<i64> });
+    /// txn.insert(Player { name: "Carol", score: Some(5) });
+    /// let leaderboard = txn.query(|rows| {
+    ///     let player = Player::join(rows);
+    ///     rows.order_by_desc_nulls_last(player.score());
+    ///     rows.into_vec(player.name())
+    /// });
+    /// assert_eq!(leaderboard, ["Carol", "Alice", "Bob"]);
+    ///
+    /// let lowest_first = txn.query(|rows| {
+    ///     let player = Player::join(rows);
+    ///     rows.order_by_asc_nulls_last(player.score());
+    ///     rows.into_vec(player.name())
+    /// });
+    /// assert_eq!(lowest_first, ["Alice", "Carol", "Bob"]);
+    /// ```
+    pub fn order_by_desc_nulls_last<T>(
+        &mut self,
+        key: impl IntoColumn<'inner, S, Typ = Option<T>>,
+    ) {
+        self.order_by_private(key, Order::Desc, Some(NullOrdering::Last))
+    }
+
+    fn order_by_private(
+        &mut self,
+        key: impl IntoColumn<'inner, S>,
+        order: Order,
+        nulls: Option<NullOrdering>,
+    ) {
+        let expr = key.into_column().inner.build_expr(self.ast.builder());
+        self.order_by.push((expr, order, nulls));
+    }
+
     /// Turn a database query into a rust [Vec] of results.
     ///
     /// Types that implement [crate::IntoColumn], will also implement [IntoDummy].
@@ -43,6 +285,7 @@ impl<'outer, 'inner, S> Query<'outer, 'inner, S> {
     ///
     /// Note that the result of `Query::into_vec` is sorted. When a `*Dummy` struct is used for
     /// the output, the sorting order depends on the order of the fields in the struct definition.
+    /// Use [Query::order_by_asc] and [Query::order_by_desc] to sort by other keys first.
     pub fn into_vec<D>(&self, dummy: D) -> Vec<D::Out>
     where
         D: IntoDummy<'inner, 'outer, S>,
@@ -51,29 +294,422 @@ impl<'outer, 'inner, S> Query<'outer, 'inner, S> {
     }
 
     pub(crate) fn into_vec_private<'x, 'l, D>(&self, dummy: D) -> Vec<D::Out>
+    where
+        D: IntoDummy<'x, 'outer, S>,
+    {
+        let mut out = vec![];
+        self.for_each_private(dummy, |x| out.push(x));
+        out
+    }
+
+    /// Turn a database query into any collection that implements [Default] and [Extend].
+    ///
+    /// This works the same as [Query::into_vec], but the results are added to the collection one by one.
+    /// For example, results that are pairs can be collected in a [std::collections::HashMap].
+    /// Later results replace earlier results with the same key, just like with [Iterator::collect].
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Product { name: String, price: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// txn.insert(Product { name: "apple", price: 3 });
+    /// txn.insert(Product { name: "pear", price: 5 });
+    /// let prices: HashMap<String, i64> = txn.query(|rows| {
+    ///     let product = Product::join(rows);
+    ///     rows.collect((product.name(), product.price()))
+    /// });
+    /// assert_eq!(prices["pear"], 5);
+    /// ```
+    pub fn collect<C, D>(&self, dummy: D) -> C
+    where
+        D: IntoDummy<'inner, 'outer, S>,
+        C: Default + Extend<D::Out>,
+    {
+        let mut out = C::default();
+        self.for_each_private(dummy, |x| out.extend([x]));
+        out
+    }
+
+    /// Call a closure for every result of a database query, without collecting them in a [Vec].
+    ///
+    /// This works the same as [Query::into_vec], but every result is passed to `f` as soon as it is read.
+    /// The results are never stored together, so this works for results that do not fit in memory.
+    /// Note that sqlite might still need to buffer the results to sort them, if it can not use an index.
+    ///
+    /// The closure is called while the sqlite statement is running.
+    /// It is fine to make other queries in the closure, they will use a different statement.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     OrderLine { amount: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// for amount in [3, 1, 2] {
+    ///     txn.insert(OrderLine { amount });
+    /// }
+    /// let mut seen = vec![];
+    /// txn.query(|rows| {
+    ///     let line = OrderLine::join(rows);
+    ///     rows.for_each(line, |line| {
+    ///         // this query runs while the outer query is still running
+    ///         seen.push(txn.query_one(line.amount()));
+    ///     });
+    /// });
+    /// assert_eq!(seen, [3, 1, 2]);
+    /// ```
+    pub fn for_each<D>(&self, dummy: D, f: impl FnMut(D::Out))
+    where
+        D: IntoDummy<'inner, 'outer, S>,
+    {
+        self.for_each_private(dummy, f)
+    }
+
+    fn for_each_private<'x, D>(&self, dummy: D, f: impl FnMut(D::Out))
     where
         D: IntoDummy<'x, 'outer, S>,
     {
         let mut cacher = Cacher::new();
-        let mut prepared = dummy.into_dummy().inner.prepare(&mut cacher);
+        let prepared = dummy.into_dummy().inner.prepare(&mut cacher);
 
         let cached = self.ast.cache(cacher.columns);
+        self.execute(self.ordered_select(&self.ast), &cached, prepared, f)
+    }
+
+    /// Get the sql and the parameter values of the query that [Query::into_vec] would execute.
+    ///
+    /// The query is not executed. This is useful for debugging and logging.
+    /// Note that the sql is not stable, it can change in any version of rust_query.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Product { name: String, price: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let txn = client.transaction(&database);
+    /// let (sql, values) = txn.query(|rows| {
+    ///     let product = Product::join(rows);
+    ///     rows.filter(product.price().lt(10));
+    ///     rows.to_sql(product.name())
+    /// });
+    /// assert!(sql.starts_with("SELECT"));
+    /// assert_eq!(values, [rusqlite::types::Value::Integer(10)]);
+    /// ```
+    pub fn to_sql<D>(&self, dummy: D) -> (String, Vec<rusqlite::types::Value>)
+    where
+        D: IntoDummy<'inner, 'outer, S>,
+    {
+        let mut cacher = Cacher::new();
+        dummy.into_dummy().inner.prepare(&mut cacher);
+        // use a copy, so that the columns are not added to the query itself
+        let ast = self.ast.clone();
+        ast.cache(cacher.columns);
+
+        let (sql, values) = self.build_sql(self.ordered_select(&ast));
+        let values = values.0.iter().map(|value| match value.to_sql().unwrap() {
+            ToSqlOutput::Borrowed(value) => value.into(),
+            ToSqlOutput::Owned(value) => value,
+            _ => unreachable!("sea_query values are borrowed or owned"),
+        });
+        (sql, values.collect())
+    }
 
-        let select = self.ast.simple();
-        let (sql, values) = select.build_rusqlite(SqliteQueryBuilder);
+    /// The select statement of the query, with all the ordering applied.
+    fn ordered_select(&self, ast: &MySelect) -> SelectStatement {
+        let mut select = ast.build_select(false);
+        for (key, order, nulls) in &self.order_by {
+            match nulls {
+                Some(nulls) => select.order_by_expr_with_nulls(key.clone(), order.clone(), *nulls),
+                None => select.order_by_expr(key.clone(), order.clone()),
+            };
+        }
+        // sorting by all columns makes the order deterministic
+        for (aggr, _alias) in ast.select.iter() {
+            select.order_by_expr(aggr.clone(), Order::Asc);
+        }
+        if self.distinct {
+            select.distinct();
+        }
+        select
+    }
+
+    /// Turn the results that are returned by both this query and the `other` query into a [Vec].
+    ///
+    /// This uses the sqlite `INTERSECT` operator.
+    /// The `other` query is constructed with a closure, which needs to return a [Dummy] of the same type
+    /// as `dummy`. This makes sure that both queries return the same type of results.
+    /// Use [IntoDummy::into_dummy] to get a [Dummy] from a value.
+    ///
+    /// The results are always sorted by all columns, the order set with
+    /// [Query::order_by_asc] and the other `order_by` methods is not used.
+    /// Note that duplicate results are removed.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, IntoDummy, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Customer { name: String, city: String },
+    /// #     Employee { name: String, city: String },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let txn = client.transaction(&database);
+    /// // cities with both customers and employees
+    /// let cities = txn.query(|rows| {
+    ///     let customer = Customer::join(rows);
+    ///     rows.into_vec_intersect(customer.city(), |rows| {
+    ///         let employee = Employee::join(rows);
+    ///         employee.city().into_dummy()
+    ///     })
+    /// });
+    /// ```
+    pub fn into_vec_intersect<D>(
+        &self,
+        dummy: D,
+        other: impl for<'x> FnOnce(&mut Rows<'x, S>) -> Dummy<'x, 'outer, S, D::Impl>,
+    ) -> Vec<D::Out>
+    where
+        D: IntoDummy<'inner, 'outer, S>,
+    {
+        self.compound_vec(dummy, UnionType::Intersect, other)
+    }
+
+    /// Turn the results that are returned by this query, but not by the `other` query, into a [Vec].
+    ///
+    /// This uses the sqlite `EXCEPT` operator.
+    /// The `other` query works the same as for [Query::into_vec_intersect].
+    ///
+    /// The results are sorted like for [Query::into_vec_intersect].
+    /// Note that duplicate results are removed.
+    pub fn into_vec_except<D>(
+        &self,
+        dummy: D,
+        other: impl for<'x> FnOnce(&mut Rows<'x, S>) -> Dummy<'x, 'outer, S, D::Impl>,
+    ) -> Vec<D::Out>
+    where
+        D: IntoDummy<'inner, 'outer, S>,
+    {
+        self.compound_vec(dummy, UnionType::Except, other)
+    }
+
+    /// Turn the results that are returned by this query or the `other` query into a [Vec].
+    ///
+    /// This uses the sqlite `UNION` operator.
+    /// The `other` query works the same as for [Query::into_vec_intersect].
+    ///
+    /// The results are sorted like for [Query::into_vec_intersect].
+    /// Note that duplicate results are removed, use [Query::into_vec_union_all] to keep them.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, IntoDummy, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Order { product: String, amount: i64 },
+    /// #     ArchivedOrder { product: String, amount: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// txn.insert(Order { product: "pear", amount: 2 });
+    /// txn.insert(ArchivedOrder { product: "apple", amount: 5 });
+    /// txn.insert(ArchivedOrder { product: "pear", amount: 2 });
+    /// // current and archived orders together
+    /// let orders = txn.query(|rows| {
+    ///     let order = Order::join(rows);
+    ///     rows.into_vec_union((order.product(), order.amount()), |rows| {
+    ///         let order = ArchivedOrder::join(rows);
+    ///         (order.product(), order.amount()).into_dummy()
+    ///     })
+    /// });
+    /// assert_eq!(orders, [("apple".to_owned(), 5), ("pear".to_owned(), 2)]);
+    /// ```
+    pub fn into_vec_union<D>(
+        &self,
+        dummy: D,
+        other: impl for<'x> FnOnce(&mut Rows<'x, S>) -> Dummy<'x, 'outer, S, D::Impl>,
+    ) -> Vec<D::Out>
+    where
+        D: IntoDummy<'inner, 'outer, S>,
+    {
+        self.compound_vec(dummy, UnionType::Distinct, other)
+    }
+
+    /// Turn the results that are returned by this query and the `other` query into a [Vec].
+    ///
+    /// This uses the sqlite `UNION ALL` operator.
+    /// It works the same as [Query::into_vec_union], but duplicate results are kept.
+    pub fn into_vec_union_all<D>(
+        &self,
+        dummy: D,
+        other: impl for<'x> FnOnce(&mut Rows<'x, S>) -> Dummy<'x, 'outer, S, D::Impl>,
+    ) -> Vec<D::Out>
+    where
+        D: IntoDummy<'inner, 'outer, S>,
+    {
+        self.compound_vec(dummy, UnionType::All, other)
+    }
+
+    fn compound_vec<D>(
+        &self,
+        dummy: D,
+        op: UnionType,
+        other: impl for<'x> FnOnce(&mut Rows<'x, S>) -> Dummy<'x, 'outer, S, D::Impl>,
+    ) -> Vec<D::Out>
+    where
+        D: IntoDummy<'inner, 'outer, S>,
+    {
+        let mut cacher = Cacher::new();
+        let prepared = dummy.into_dummy().inner.prepare(&mut cacher);
+        let (mut select, fields) = self.ast.build_compound_part(cacher.columns);
+
+        let mut rows = Rows {
+            phantom: PhantomData,
+            _p: PhantomData,
+            ast: MySelect::default(),
+        };
+        let mut other_cacher = Cacher::new();
+        // the other dummy has the same type, so it will have the same columns
+        other(&mut rows).inner.prepare(&mut other_cacher);
+        let (other_select, _) = rows.ast.build_compound_part(other_cacher.columns);
+
+        select.union(op, other_select);
+        for field in &fields {
+            select.order_by(*field, Order::Asc);
+        }
+        let mut out = vec![];
+        self.execute(select, &fields, prepared, |x| out.push(x));
+        out
+    }
+
+    /// Add the limit and offset to the select statement and build the sql.
+    fn build_sql(&self, mut select: SelectStatement) -> (String, RusqliteValues) {
+        // sqlite uses signed integers, larger values would fail to bind
+        let clamp = |n: u64| n.min(i64::MAX as u64);
+        if let Some(offset) = self.offset {
+            // sqlite only allows `OFFSET` together with `LIMIT`
+            select.limit(clamp(self.limit.unwrap_or(u64::MAX)));
+            select.offset(clamp(offset));
+        } else if let Some(limit) = self.limit {
+            select.limit(clamp(limit));
+        }
+        select.build_rusqlite(SqliteQueryBuilder)
+    }
+
+    fn execute<P: Prepared>(
+        &self,
+        select: SelectStatement,
+        fields: &[Field],
+        mut prepared: P,
+        mut f: impl FnMut(P::Out),
+    ) {
+        let (sql, values) = self.build_sql(select);
         if SHOW_SQL.get() {
             println!("{sql}");
             println!("{values:?}");
         }
 
-        let mut statement = self.conn.prepare_cached(&sql).unwrap();
+        if let Some(warn) = TABLE_SCAN.get() {
+            for detail in table_scans(self.conn, &sql, &values) {
+                warn(&sql, &detail);
+            }
+        }
+
+        let mut statement = prepare_cached(self.conn, &sql);
         let mut rows = statement.query(&*values.as_params()).unwrap();
 
-        let mut out = vec![];
         while let Some(row) = rows.next().unwrap() {
-            out.push(prepared.call(Row::new(row, &cached)));
+            f(prepared.call(Row::new(row, fields)));
         }
-        out
+    }
+}
+
+/// Find the lines of the query plan that read a full table.
+///
+/// `SCAN` reads the full table, even if it uses an index for the order of the rows.
+fn table_scans(conn: &rusqlite::Connection, sql: &str, values: &RusqliteValues) -> Vec<String> {
+    let plan_sql = format!("EXPLAIN QUERY PLAN {sql}");
+    let mut statement = prepare_cached(conn, &plan_sql);
+    let mut rows = statement.query(&*values.as_params()).unwrap();
+
+    let mut subqueries = vec![];
+    let mut scans = vec![];
+    while let Some(row) = rows.next().unwrap() {
+        let detail: String = row.get("detail").unwrap();
+        let subquery = detail
+            .strip_prefix("MATERIALIZE ")
+            .or(detail.strip_prefix("CO-ROUTINE "));
+        if let Some(name) = subquery {
+            subqueries.push(name.to_owned());
+        }
+        let Some(scan) = detail.strip_prefix("SCAN ") else {
+            continue;
+        };
+        let name = scan.split(' ').next().unwrap();
+        // `VALUES` has no name and `CONSTANT ROW` is used for queries without tables.
+        // Subqueries are already computed and virtual tables (like pragmas) can not have an index.
+        if name.is_empty()
+            || scan == "CONSTANT ROW"
+            || subqueries.iter().any(|x| x == name)
+            || scan.contains("VIRTUAL TABLE")
+        {
+            continue;
+        }
+        scans.push(detail);
+    }
+    scans
+}
+
+pub(crate) type TableScanHook = Box<dyn Fn(&str, &str) + Send + Sync>;
+
+/// Set by [crate::migration::Config::warn_on_table_scan].
+/// This can be global, because a [crate::migration::Config] can only be opened once per process.
+pub(crate) static TABLE_SCAN: OnceLock<TableScanHook> = OnceLock::new();
+
+/// Functions that are only available if sqlite is compiled with `SQLITE_ENABLE_MATH_FUNCTIONS`.
+const MATH_FUNCTIONS: &[&str] = &["sqrt", "pow"];
+
+/// Set by [crate::LocalClient::migrator] when the first connection is opened.
+/// See [TABLE_SCAN] for why this can be global.
+pub(crate) static HAS_MATH_FUNCTIONS: OnceLock<bool> = OnceLock::new();
+
+/// Check if the linked sqlite library has the [MATH_FUNCTIONS].
+pub(crate) fn has_math_functions(conn: &rusqlite::Connection) -> bool {
+    conn.prepare("SELECT sqrt(1.0), pow(1.0, 1.0)").is_ok()
+}
+
+/// Prepare a statement, giving a clear error message if a math function is not available.
+pub(crate) fn prepare_cached<'c>(
+    conn: &'c rusqlite::Connection,
+    sql: &str,
+) -> rusqlite::CachedStatement<'c> {
+    match conn.prepare_cached(sql) {
+        Ok(stmt) => stmt,
+        Err(
+            rusqlite::Error::SqliteFailure(_, Some(msg))
+            | rusqlite::Error::SqlInputError { msg, .. },
+        ) if HAS_MATH_FUNCTIONS.get() == Some(&false)
+            && MATH_FUNCTIONS
+                .iter()
+                .any(|name| msg == format!("no such function: {name}")) =>
+        {
+            panic!(
+                "{msg}. The sqlite library that is used does not have math functions, \
+                they are only available if sqlite is compiled with `SQLITE_ENABLE_MATH_FUNCTIONS`."
+            )
+        }
+        Err(err) => Err(err).unwrap(),
     }
 }
 
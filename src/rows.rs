@@ -3,9 +3,10 @@ use std::marker::PhantomData;
 use sea_query::{Expr, SimpleExpr};
 
 use crate::{
+    alias::{Field, MyAlias},
     ast::MySelect,
     db::Join,
-    value::{operations::Assume, IntoColumn, Typed},
+    value::{operations::Assume, EqTyp, IntoColumn, Typed, ValueBuilder},
     Column, Table,
 };
 
@@ -32,7 +33,8 @@ impl<'inner, S> Rows<'inner, S> {
     /// For convenience there is also [Table::join].
     pub fn join<T: Table<Schema = S>>(&mut self) -> Column<'inner, S, T> {
         let alias = self.ast.scope.new_alias();
-        self.ast.tables.push((T::NAME.to_owned(), alias));
+        // the name is quoted, because it could be a keyword like `order`
+        self.ast.tables.push((format!("\"{}\"", T::NAME), alias));
         Column::new(Join::new(alias))
     }
 
@@ -42,6 +44,132 @@ impl<'inner, S> Rows<'inner, S> {
         Column::new(Join::new(alias))
     }
 
+    /// Join the distinct values of a column in a sub-query.
+    ///
+    /// After this operation [Rows] has rows for the combinations of each original row with each distinct value.
+    /// The sub-query uses a `GROUP BY` on the column, so the column can be any expression, not just a table row.
+    ///
+    /// This is useful together with [crate::aggregate] to group rows by a computed value.
+    /// [crate::args::Aggregate::filter_on] with the same expression then gives an aggregate for every group.
+    /// ```
+    /// # use rust_query::{aggregate, call_fn, migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Customer { name: String, zip: String },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// txn.insert(Customer { name: "Alice", zip: "12345" });
+    /// txn.insert(Customer { name: "Bob", zip: "12399" });
+    /// txn.insert(Customer { name: "Carol", zip: "98765" });
+    /// let per_area = txn.query(|rows| {
+    ///     let area = rows.join_distinct(|rows| {
+    ///         let customer = Customer::join(rows);
+    ///         call_fn::<String, _>("substr", (customer.zip(), 1, 3))
+    ///     });
+    ///     let count = aggregate(|rows| {
+    ///         let customer = Customer::join(rows);
+    ///         let customer_area = call_fn::<String, _>("substr", (customer.zip(), 1, 3));
+    ///         rows.filter_on(customer_area, &area);
+    ///         rows.count()
+    ///     });
+    ///     rows.into_vec((area, count))
+    /// });
+    /// assert_eq!(per_area, [("123".to_owned(), 2), ("987".to_owned(), 1)]);
+    /// ```
+    pub fn join_distinct<T: EqTyp + 'static>(
+        &mut self,
+        f: impl for<'a> FnOnce(&mut Rows<'a, S>) -> Column<'a, S, T>,
+    ) -> Column<'inner, S, T> {
+        let mut rows = Rows {
+            phantom: PhantomData,
+            _p: PhantomData,
+            ast: MySelect::default(),
+        };
+        let key = f(&mut rows).inner;
+        let key = key.build_expr(rows.ast.builder());
+        let field = rows.ast.scope.new_alias();
+        rows.ast.filter_on.push(Box::new((key, field)));
+
+        let table = self.ast.scope.new_alias();
+        self.ast
+            .subqueries
+            .push((rows.ast.build_select(true), table));
+        Column::new(Distinct {
+            table,
+            field,
+            _p: PhantomData,
+        })
+    }
+
+    /// Join the rows of a sub-query that returns two columns.
+    ///
+    /// After this operation [Rows] has rows for the combinations of each original row with each row of the sub-query.
+    /// Both columns of the sub-query can be used like normal columns afterwards.
+    ///
+    /// This is mostly useful for filtering on [crate::window] functions, which sqlite only allows
+    /// outside of the query that computes them. For example, to find the longest track of every genre:
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, window, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Track { genre: String, name: String, milliseconds: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// txn.insert(Track { genre: "Rock", name: "Long", milliseconds: 300 });
+    /// txn.insert(Track { genre: "Rock", name: "Short", milliseconds: 100 });
+    /// txn.insert(Track { genre: "Jazz", name: "Solo", milliseconds: 200 });
+    /// let longest = txn.query(|rows| {
+    ///     let (track, position) = rows.join_subquery(|rows| {
+    ///         let track = Track::join(rows);
+    ///         let position = window(|w| {
+    ///             w.partition_by(track.genre());
+    ///             w.order_by_desc(track.milliseconds());
+    ///             w.row_number()
+    ///         });
+    ///         (track, position)
+    ///     });
+    ///     rows.filter(position.eq(1));
+    ///     rows.order_by_asc(track.genre());
+    ///     rows.into_vec((track.genre(), track.name()))
+    /// });
+    /// assert_eq!(longest, [("Jazz".to_owned(), "Solo".to_owned()), ("Rock".to_owned(), "Long".to_owned())]);
+    /// ```
+    pub fn join_subquery<A: 'static, B: 'static>(
+        &mut self,
+        f: impl for<'a> FnOnce(&mut Rows<'a, S>) -> (Column<'a, S, A>, Column<'a, S, B>),
+    ) -> (Column<'inner, S, A>, Column<'inner, S, B>) {
+        let mut rows = Rows {
+            phantom: PhantomData,
+            _p: PhantomData,
+            ast: MySelect::default(),
+        };
+        let (a, b) = f(&mut rows);
+        let fields = rows.ast.cache([a.inner.erase(), b.inner.erase()]);
+
+        let table = self.ast.scope.new_alias();
+        self.ast
+            .subqueries
+            .push((rows.ast.build_select(false), table));
+        (
+            Column::new(SubqueryColumn {
+                table,
+                field: fields[0],
+                _p: PhantomData,
+            }),
+            Column::new(SubqueryColumn {
+                table,
+                field: fields[1],
+                _p: PhantomData,
+            }),
+        )
+    }
+
     // Join a vector of values.
     // pub fn vec<V: Column<'inner>>(&mut self, vec: Vec<V>) -> Join<'inner, V::Typ> {
     //     todo!()
@@ -53,6 +181,44 @@ impl<'inner, S> Rows<'inner, S> {
         self.filter_private(prop.build_expr(self.ast.builder()));
     }
 
+    /// Filter rows where at least one of the columns is true.
+    ///
+    /// This is useful when the number of conditions is only known at runtime.
+    /// All rows are filtered out if there are no columns.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     User { name: String, email: String },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// txn.insert(User { name: "alice", email: "alice@example.com" });
+    /// txn.insert(User { name: "bob", email: "bob@example.com" });
+    /// txn.insert(User { name: "carol", email: "bob@other.com" });
+    /// let search = |terms: &[&str]| txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.filter_any(terms.iter().map(|term| user.email().starts_with(term)));
+    ///     rows.into_vec(user.name())
+    /// });
+    /// assert_eq!(search(&["alice", "bob@other"]), ["alice", "carol"]);
+    /// assert_eq!(search(&[]), Vec::<String>::new());
+    /// ```
+    pub fn filter_any(
+        &mut self,
+        props: impl IntoIterator<Item = impl IntoColumn<'inner, S, Typ = bool>>,
+    ) {
+        let b = self.ast.builder();
+        let any = props
+            .into_iter()
+            .map(|prop| prop.into_column().inner.build_expr(b))
+            .reduce(SimpleExpr::or)
+            .unwrap_or(false.into());
+        self.filter_private(any);
+    }
+
     fn filter_private(&mut self, prop: SimpleExpr) {
         self.ast.filters.push(Box::new(prop));
     }
@@ -69,3 +235,29 @@ impl<'inner, S> Rows<'inner, S> {
         Column::new(Assume(val))
     }
 }
+
+struct Distinct<T> {
+    table: MyAlias,
+    field: MyAlias,
+    _p: PhantomData<T>,
+}
+
+impl<T> Typed for Distinct<T> {
+    type Typ = T;
+    fn build_expr(&self, _: ValueBuilder) -> SimpleExpr {
+        Expr::col((self.table, self.field)).into()
+    }
+}
+
+struct SubqueryColumn<T> {
+    table: MyAlias,
+    field: Field,
+    _p: PhantomData<T>,
+}
+
+impl<T> Typed for SubqueryColumn<T> {
+    type Typ = T;
+    fn build_expr(&self, _: ValueBuilder) -> SimpleExpr {
+        Expr::col((self.table, self.field)).into()
+    }
+}
@@ -58,6 +58,19 @@ impl TableListDummy<Column<'_, Pragma, TableList>> {
 
 table! {TableList, TableListDummy, _ => "pragma_table_list".to_owned()}
 
+struct SqliteSchema;
+
+#[repr(transparent)]
+#[derive(RefCast)]
+struct SqliteSchemaDummy<T>(T);
+
+impl SqliteSchemaDummy<Column<'_, Pragma, SqliteSchema>> {
+    field! {name: String}
+    field! {sql: Option<String>}
+}
+
+table! {SqliteSchema, SqliteSchemaDummy, _ => "sqlite_schema".to_owned()}
+
 struct TableInfo(pub String);
 
 #[repr(transparent)]
@@ -69,9 +82,12 @@ impl TableInfoDummy<Column<'_, Pragma, TableInfo>> {
     field! {r#type("type"): String}
     field! {notnull: i64}
     field! {pk: i64}
+    field! {dflt_value: Option<String>}
+    field! {hidden: i64}
 }
 
-table! {TableInfo, TableInfoDummy, val => format!("pragma_table_info('{}', 'main')", val.0)}
+// `table_xinfo` also includes generated columns
+table! {TableInfo, TableInfoDummy, val => format!("pragma_table_xinfo('{}', 'main')", val.0)}
 
 struct ForeignKeyList(pub String);
 
@@ -123,6 +139,8 @@ pub fn read_schema(conn: &Transaction<Pragma>) -> hash::Schema {
         r#type: String,
         pk: i64,
         notnull: i64,
+        dflt_value: Option<String>,
+        hidden: i64,
     }
 
     let tables = conn.query(|q| {
@@ -149,10 +167,22 @@ pub fn read_schema(conn: &Transaction<Pragma>) -> hash::Schema {
             .into_iter()
             .collect();
 
+        // sqlite has no pragma for check constraints, so we read them from the sql
+        let sql = conn.query(|q| {
+            let table = q.join_custom(SqliteSchema);
+            q.filter(table.name().eq(&table_name));
+            let sql = q.filter_some(table.sql());
+            q.into_vec(sql)
+        });
+        let sql = sql.first().map(|x| x.as_str()).unwrap_or_default();
+        let checks = column_clauses(sql, "CHECK (");
+        let generated = column_clauses(sql, "GENERATED ALWAYS AS (");
+
         let make_type = |col: &Column| match col.r#type.as_str() {
             "INTEGER" => hash::ColumnType::Integer,
             "TEXT" => hash::ColumnType::String,
             "REAL" => hash::ColumnType::Float,
+            "BLOB" => hash::ColumnType::Blob,
             t => panic!("unknown type {t}"),
         };
 
@@ -170,8 +200,15 @@ pub fn read_schema(conn: &Transaction<Pragma>) -> hash::Schema {
             let def = hash::Column {
                 fk: fks.get(&col.name).map(|x| (x.clone(), "id".to_owned())),
                 typ: make_type(&col),
-                name: col.name,
                 nullable: col.notnull == 0,
+                check: checks.get(&col.name).cloned(),
+                default: col.dflt_value,
+                // hidden is 2 for virtual and 3 for stored generated columns
+                generated: generated.get(&col.name).map(|expr| hash::Generated {
+                    expr: expr.clone(),
+                    stored: col.hidden == 3,
+                }),
+                name: col.name,
             };
             table_def.columns.insert(def)
         }
@@ -202,3 +239,58 @@ pub fn read_schema(conn: &Transaction<Pragma>) -> hash::Schema {
     }
     output
 }
+
+/// Find the expression after `keyword` for each column in a `CREATE TABLE` statement.
+/// This is used for check constraints and generated columns.
+///
+/// This only needs to understand the statements that we generate ourselves.
+fn column_clauses(sql: &str, keyword: &str) -> HashMap<String, String> {
+    let mut res = HashMap::new();
+    let Some(start) = sql.find('(') else {
+        return res;
+    };
+    for def in split_top_level(&sql[start + 1..]) {
+        let Some(def) = def.trim().strip_prefix('"') else {
+            continue;
+        };
+        let Some((name, rest)) = def.split_once('"') else {
+            continue;
+        };
+        let Some(clause) = rest.find(keyword).map(|i| &rest[i + keyword.len()..]) else {
+            continue;
+        };
+        if let Some(clause) = split_top_level(clause).into_iter().next() {
+            res.insert(name.to_owned(), clause.to_owned());
+        }
+    }
+    res
+}
+
+/// Split on commas that are not nested in parentheses or quotes.
+/// Stops at the closing parenthesis of the current level.
+fn split_top_level(sql: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in sql.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') if depth == 0 => {
+                parts.push(&sql[start..i]);
+                return parts;
+            }
+            (None, ')') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                parts.push(&sql[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&sql[start..]);
+    parts
+}
@@ -1,9 +1,10 @@
-use std::{convert::Infallible, marker::PhantomData, ops::Deref};
+use std::{collections::HashMap, convert::Infallible, marker::PhantomData, ops::Deref};
 
 use ref_cast::{ref_cast_custom, RefCastCustom};
 use rusqlite::ErrorCode;
 use sea_query::{
-    Alias, DeleteStatement, Expr, InsertStatement, SqliteQueryBuilder, UpdateStatement, Value,
+    Alias, Asterisk, DeleteStatement, Expr, Func, InsertStatement, ReturningClause,
+    SelectStatement, SimpleExpr, SqliteQueryBuilder, UnionType, UpdateStatement, Value,
 };
 use sea_query_rusqlite::RusqliteBinder;
 
@@ -11,11 +12,12 @@ use crate::{
     alias::Field,
     ast::MySelect,
     client::LocalClient,
+    dummy_impl::{Cacher, DummyImpl, Prepared, Row},
     migrate::schema_version,
-    query::Query,
-    value::SecretFromSql,
+    query::{prepare_cached, Query},
+    value::{trivial::FromColumn, SecretFromSql, Typed, ValueBuilder},
     writable::{Reader, Writable},
-    IntoColumn, IntoDummy, Rows, Table, TableRow,
+    Column, Dummy, IntoColumn, IntoDummy, Rows, Table, TableRow,
 };
 
 /// [Database] is a proof that the database has been configured.
@@ -62,6 +64,16 @@ impl<S> Database<S> {
 ///
 /// All [TableRow] references retrieved from the database live for at most `'a`.
 /// This makes these references effectively local to this [Transaction].
+///
+/// # Snapshot
+///
+/// All queries executed with the same [Transaction] read from the same snapshot of the database.
+/// [TransactionMut]s that are committed after the [Transaction] was created are not visible.
+/// This makes it possible to run multiple queries that need a consistent view, e.g. for a report.
+///
+/// Note that holding on to a [Transaction] prevents sqlite from
+/// [checkpointing](https://www.sqlite.org/wal.html#checkpointing) the WAL file past the snapshot.
+/// Drop the [Transaction] promptly to prevent the WAL file from growing without bound.
 #[derive(RefCastCustom)]
 #[repr(transparent)]
 pub struct Transaction<'a, S> {
@@ -94,6 +106,30 @@ impl<'a, S> Deref for TransactionMut<'a, S> {
     }
 }
 
+/// Same as [Transaction], but sqlite makes sure that nothing is written.
+///
+/// This is created with [LocalClient::transaction_readonly].
+/// All methods of [Transaction] can be used through [Deref].
+pub struct TransactionReadonly<'a, S> {
+    pub(crate) inner: Transaction<'a, S>,
+    pub(crate) conn: &'a rusqlite::Connection,
+}
+
+impl<'a, S> Deref for TransactionReadonly<'a, S> {
+    type Target = Transaction<'a, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<S> Drop for TransactionReadonly<'_, S> {
+    fn drop(&mut self) {
+        // the pragma is not part of the transaction, so it can be reset before the transaction ends
+        self.conn.pragma_update(None, "query_only", false).unwrap();
+    }
+}
+
 impl<'t, S> Transaction<'t, S> {
     /// This will check the schema version and panic if it is not as expected
     pub(crate) fn new_checked(txn: rusqlite::Transaction<'t>, expected: i64) -> Self {
@@ -130,6 +166,10 @@ impl<'t, S> Transaction<'t, S> {
             q,
             phantom: PhantomData,
             conn,
+            limit: None,
+            offset: None,
+            distinct: false,
+            order_by: vec![],
         })
     }
 
@@ -137,6 +177,10 @@ impl<'t, S> Transaction<'t, S> {
     ///
     /// Instead of using [Self::query_one] in a loop, it is better to
     /// call [Self::query] and return all results at once.
+    ///
+    /// This never panics, because the query does not join any tables and thus always has exactly one row.
+    /// The same holds for an [crate::aggregate] that is used directly, it has a result even when
+    /// the table it aggregates is empty.
     pub fn query_one<'e, O>(&self, val: impl IntoDummy<'t, 't, S, Out = O>) -> O
     where
         S: 'static,
@@ -150,6 +194,230 @@ impl<'t, S> Transaction<'t, S> {
         });
         res.pop().unwrap()
     }
+
+    /// Retrieve the first result of a query, or [None] if the query has no results.
+    ///
+    /// Unlike [Self::query_one], the query can join tables and filter rows, so it might not have any results.
+    /// The closure needs to return a [crate::Dummy], use [IntoDummy::into_dummy] to get one from a value.
+    /// If there are multiple results, the first one is returned in the same order as [Query::into_vec].
+    ///
+    /// Note that [None] means that there are no rows.
+    /// A row where the selected column is `NULL` is returned as `Some(None)`.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, IntoDummy, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Warehouse { name: String, manager: Option<String> },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// txn.insert(Warehouse { name: "North", manager: None::<String> });
+    ///
+    /// let manager = txn.query_opt(|rows| {
+    ///     let warehouse = Warehouse::join(rows);
+    ///     rows.filter(warehouse.name().eq("North"));
+    ///     warehouse.manager().into_dummy()
+    /// });
+    /// assert_eq!(manager, Some(None));
+    ///
+    /// let manager = txn.query_opt(|rows| {
+    ///     let warehouse = Warehouse::join(rows);
+    ///     rows.filter(warehouse.name().eq("South"));
+    ///     warehouse.manager().into_dummy()
+    /// });
+    /// assert_eq!(manager, None);
+    /// ```
+    pub fn query_opt<I: DummyImpl>(
+        &self,
+        f: impl for<'inner> FnOnce(&mut Rows<'inner, S>) -> Dummy<'inner, 't, S, I>,
+    ) -> Option<I::Out> {
+        self.query(|rows| {
+            let dummy = f(rows);
+            rows.limit(1);
+            rows.into_vec_private(dummy).pop()
+        })
+    }
+
+    /// Return the number of rows in a table.
+    ///
+    /// This uses a single `SELECT COUNT(*)` query, see [Self::count_where] to count only some rows.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     User { name: String, age: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// assert_eq!(txn.count::<User>(), 0);
+    /// txn.insert(User { name: "Alice", age: 30 });
+    /// txn.insert(User { name: "Bob", age: 12 });
+    /// assert_eq!(txn.count::<User>(), 2);
+    /// ```
+    pub fn count<T: Table<Schema = S>>(&self) -> i64 {
+        self.count_where(|rows| {
+            rows.join::<T>();
+        })
+    }
+
+    /// Return the number of rows of a query.
+    ///
+    /// The closure receives the [Rows] of the query, it can join tables and filter rows.
+    /// This uses a single `SELECT COUNT(*)` query, without retrieving the rows.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     User { name: String, age: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// txn.insert(User { name: "Alice", age: 30 });
+    /// txn.insert(User { name: "Bob", age: 12 });
+    /// let minors = txn.count_where(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.filter(user.age().lt(18));
+    /// });
+    /// assert_eq!(minors, 1);
+    /// ```
+    pub fn count_where(&self, f: impl for<'inner> FnOnce(&mut Rows<'inner, S>)) -> i64 {
+        let mut rows = Rows {
+            phantom: PhantomData,
+            ast: MySelect::default(),
+            _p: PhantomData,
+        };
+        f(&mut rows);
+        let mut select = rows.ast.build_source();
+        select.expr(Func::count(Expr::col(Asterisk)));
+
+        let (sql, values) = select.build_rusqlite(SqliteQueryBuilder);
+        let mut statement = prepare_cached(&self.transaction, &sql);
+        statement
+            .query_row(&*values.as_params(), |row| row.get(0))
+            .unwrap()
+    }
+
+    /// Check if the row of an [crate::UncheckedRow] exists and turn it into a [TableRow].
+    ///
+    /// Returns [None] if the row does not exist in this transaction.
+    #[cfg(feature = "serde")]
+    pub fn check_row<T: Table<Schema = S>>(
+        &self,
+        row: crate::UncheckedRow<T>,
+    ) -> Option<TableRow<'t, T>> {
+        let mut rows = self.query(|rows| {
+            let table = T::join(rows);
+            rows.filter(table.eq(Column::new(row.inner)));
+            rows.into_vec(table)
+        });
+        rows.pop()
+    }
+
+    /// Retrieve the value `O` for every row in `ids`, in the same order as `ids`.
+    ///
+    /// This is useful when the order of the rows is decided outside of the database.
+    /// All rows are retrieved with a few queries using the sqlite `IN` operator,
+    /// instead of a separate query for every row.
+    ///
+    /// Every [TableRow] is a proof that the row exists in this transaction, so there is
+    /// a result for every row. A row that is in `ids` multiple times is also returned multiple times.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, Dummy, LocalClient};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Product { name: String, price: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// #[derive(Dummy, Clone, PartialEq, Debug)]
+    /// #[rust_query(From = Product)]
+    /// struct ProductInfo {
+    ///     name: String,
+    ///     price: i64,
+    /// }
+    ///
+    /// let apple = txn.insert(Product { name: "apple", price: 3 });
+    /// let pear = txn.insert(Product { name: "pear", price: 4 });
+    ///
+    /// let products: Vec<ProductInfo> = txn.fetch_many(&[pear, apple, pear]);
+    /// let names: Vec<_> = products.iter().map(|x| &*x.name).collect();
+    /// assert_eq!(names, ["pear", "apple", "pear"]);
+    /// ```
+    pub fn fetch_many<T, O>(&self, ids: &[TableRow<'t, T>]) -> Vec<O>
+    where
+        T: Table<Schema = S>,
+        O: FromColumn<'t, S, T> + Clone,
+    {
+        let mut found = HashMap::new();
+        for chunk in ids.chunks(MAX_VARIABLE_NUMBER) {
+            let res = self.query(|rows| {
+                let table = T::join(rows);
+                rows.filter(table.in_values(chunk.iter().copied()));
+                rows.into_vec_private((table.clone(), table.into_trivial::<O>()))
+            });
+            for (row, val) in res {
+                found.insert(row.inner.idx, val);
+            }
+        }
+        ids.iter()
+            .map(|row| found[&row.inner.idx].clone())
+            .collect()
+    }
+
+    /// Retrieve the first result that is [Some] from a list of optional values.
+    ///
+    /// Every value is retrieved with [Self::query_one] in turn, until one of them is [Some].
+    /// The remaining values are not retrieved.
+    /// This is useful to try a lookup and fall back to another lookup if the first one found nothing.
+    ///
+    /// All values need to have the same type, so it can help to convert them with [IntoColumn::into_column].
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Customer {
+    /// #         #[unique_by_email]
+    /// #         email: String,
+    /// #         #[unique_by_name]
+    /// #         name: String,
+    /// #     },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// let alice = txn.try_insert(Customer { email: "alice@example.com", name: "Alice" }).unwrap();
+    ///
+    /// let ident = "Alice";
+    /// let customer = txn.query_one_first_of([
+    ///     Customer::unique_by_email(ident),
+    ///     Customer::unique_by_name(ident),
+    /// ]);
+    /// assert_eq!(customer, Some(alice));
+    ///
+    /// let customer = txn.query_one_first_of([
+    ///     Customer::unique_by_email("Bob"),
+    ///     Customer::unique_by_name("Bob"),
+    /// ]);
+    /// assert_eq!(customer, None);
+    /// ```
+    pub fn query_one_first_of<O>(
+        &self,
+        vals: impl IntoIterator<Item = impl IntoDummy<'t, 't, S, Out = Option<O>>>,
+    ) -> Option<O>
+    where
+        S: 'static,
+    {
+        vals.into_iter().find_map(|val| self.query_one(val))
+    }
 }
 
 impl<'t, S: 'static> TransactionMut<'t, S> {
@@ -164,6 +432,31 @@ impl<'t, S: 'static> TransactionMut<'t, S> {
         &mut self,
         val: impl Writable<'t, T = T, Conflict = C, Schema = S>,
     ) -> Result<TableRow<'t, T>, C> {
+        self.try_insert_returning(val, |row| row.into_dummy())
+    }
+
+    /// This is a convenience function to make using [TransactionMut::try_insert]
+    /// easier for tables without unique constraints.
+    ///
+    /// The new row is added to the table and the row reference is returned.
+    pub fn insert<T: Table<Schema = S>>(
+        &mut self,
+        val: impl Writable<'t, T = T, Conflict = Infallible, Schema = S>,
+    ) -> TableRow<'t, T> {
+        let Ok(row) = self.try_insert(val);
+        row
+    }
+
+    /// Same as [TransactionMut::try_insert], but returns the result of `f` for the new row.
+    ///
+    /// This is useful for columns that are filled in by the database, like default values and generated columns.
+    /// The result of `f` is retrieved with `INSERT ... RETURNING`, so it is part of the same statement.
+    /// The closure needs to return a [crate::Dummy], use [IntoDummy::into_dummy] to get one from a value.
+    pub fn try_insert_returning<T: Table<Schema = S>, C, I: DummyImpl>(
+        &mut self,
+        val: impl Writable<'t, T = T, Conflict = C, Schema = S>,
+        f: impl for<'x> FnOnce(Column<'x, S, T>) -> Dummy<'x, 't, S, I>,
+    ) -> Result<I::Out, C> {
         let ast = MySelect::default();
 
         let reader = Reader {
@@ -176,23 +469,26 @@ impl<'t, S: 'static> TransactionMut<'t, S> {
         let select = ast.simple();
 
         let mut insert = InsertStatement::new();
-        let names = ast.select.iter().map(|(_field, name)| *name);
         insert.into_table(Alias::new(T::NAME));
-        insert.columns(names);
-        insert.select_from(select).unwrap();
-        insert.returning_col(Alias::new(T::ID));
+        if ast.select.is_empty() {
+            // all columns use their default value
+            insert.or_default_values();
+        } else {
+            let names = ast.select.iter().map(|(_field, name)| *name);
+            insert.columns(names);
+            insert.select_from(select).unwrap();
+        }
+        let (returning, fields, mut prepared) = returning(f(Column::new(NewRow(PhantomData))));
+        insert.returning(ReturningClause::Exprs(returning));
 
         let (sql, values) = insert.build_rusqlite(SqliteQueryBuilder);
 
-        let mut statement = self.transaction.prepare_cached(&sql).unwrap();
-        let mut res = statement
-            .query_map(&*values.as_params(), |row| {
-                Ok(TableRow::<'_, T>::from_sql(row.get_ref(T::ID)?)?)
-            })
-            .unwrap();
+        let mut statement = prepare_cached(&self.transaction, &sql);
+        let mut rows = statement.query(&*values.as_params()).unwrap();
 
-        match res.next().unwrap() {
-            Ok(id) => Ok(id),
+        match rows.next() {
+            Ok(Some(row)) => Ok(prepared.call(Row::new(row, &fields))),
+            Ok(None) => unreachable!("an insert without conflict returns one row"),
             Err(rusqlite::Error::SqliteFailure(kind, Some(_val)))
                 if kind.code == ErrorCode::ConstraintViolation =>
             {
@@ -204,16 +500,35 @@ impl<'t, S: 'static> TransactionMut<'t, S> {
         }
     }
 
-    /// This is a convenience function to make using [TransactionMut::try_insert]
+    /// This is a convenience function to make using [TransactionMut::try_insert_returning]
     /// easier for tables without unique constraints.
-    ///
-    /// The new row is added to the table and the row reference is returned.
-    pub fn insert<T: Table<Schema = S>>(
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, IntoDummy, LocalClient};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Post {
+    /// #         title: String,
+    /// #         #[default("draft")]
+    /// #         status: String,
+    /// #     },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// let (post, status) = txn.insert_returning(Post { title: "Hello", status: () }, |post| {
+    ///     (post.clone(), post.status()).into_dummy()
+    /// });
+    /// assert_eq!(status, "draft");
+    /// assert_eq!(txn.query_one(post.title()), "Hello");
+    /// ```
+    pub fn insert_returning<T: Table<Schema = S>, I: DummyImpl>(
         &mut self,
         val: impl Writable<'t, T = T, Conflict = Infallible, Schema = S>,
-    ) -> TableRow<'t, T> {
-        let Ok(row) = self.try_insert(val);
-        row
+        f: impl for<'x> FnOnce(Column<'x, S, T>) -> Dummy<'x, 't, S, I>,
+    ) -> I::Out {
+        let Ok(out) = self.try_insert_returning(val, f);
+        out
     }
 
     /// This is a convenience function to make using [TransactionMut::try_insert]
@@ -232,6 +547,141 @@ impl<'t, S: 'static> TransactionMut<'t, S> {
         }
     }
 
+    /// Try inserting many values into the database at once.
+    ///
+    /// The values are inserted using as few `INSERT ... SELECT ... UNION ALL SELECT ...` statements as possible.
+    /// This is a lot faster than calling [TransactionMut::try_insert] for every value.
+    /// Returns [Ok] with references to the new rows, in the same order as the values.
+    ///
+    /// If one of the values is in conflict with an existing row (or with another value in the batch),
+    /// then [Err] is returned with the conflict information of the first conflicting value.
+    /// The conflict information is the same as for [TransactionMut::try_insert].
+    /// In that case all values before the conflicting value are inserted and the other values are not.
+    pub fn try_insert_batch<T: Table<Schema = S>, C>(
+        &mut self,
+        vals: impl IntoIterator<Item = impl Writable<'t, T = T, Conflict = C, Schema = S>>,
+    ) -> Result<Vec<TableRow<'t, T>>, C> {
+        let vals: Vec<_> = vals.into_iter().collect();
+
+        let mut names = vec![];
+        let mut parts = vec![];
+        for val in &vals {
+            let ast = MySelect::default();
+            let reader = Reader {
+                ast: &ast,
+                _p: PhantomData,
+                _p2: PhantomData,
+            };
+            val.read(reader);
+
+            // all values have the same type, so they set the same columns
+            names = ast.select.iter().map(|(_field, name)| *name).collect();
+            let select = ast.build_select(false);
+            let (_, values) = select.build_rusqlite(SqliteQueryBuilder);
+            parts.push((select, values.0.len()));
+        }
+
+        let mut res = Vec::with_capacity(vals.len());
+        let mut start = 0;
+        while start < parts.len() {
+            let mut params = parts[start].1;
+            let mut end = start + 1;
+            while let Some((_, other_params)) = parts.get(end) {
+                if end - start >= MAX_COMPOUND_SELECT || params + other_params > MAX_VARIABLE_NUMBER
+                {
+                    break;
+                }
+                params += other_params;
+                end += 1;
+            }
+
+            // `DEFAULT VALUES` can only be used to insert a single row
+            let chunk = if names.is_empty() {
+                None
+            } else {
+                let selects = parts[start..end].iter().map(|x| x.0.clone());
+                self.insert_chunk::<T>(&names, selects)
+            };
+            match chunk {
+                Some(rows) => res.extend(rows),
+                None => {
+                    // the chunk was not inserted, so we insert one row at a time to find the conflict
+                    for val in &vals[start..end] {
+                        res.push(self.try_insert(val)?);
+                    }
+                }
+            }
+            start = end;
+        }
+        Ok(res)
+    }
+
+    /// Insert all the selects as one statement.
+    ///
+    /// Returns [None] if there was a constraint violation, in that case nothing is inserted.
+    fn insert_chunk<T: Table<Schema = S>>(
+        &mut self,
+        names: &[Field],
+        mut selects: impl Iterator<Item = SelectStatement>,
+    ) -> Option<Vec<TableRow<'t, T>>> {
+        let mut insert = InsertStatement::new();
+        insert.into_table(Alias::new(T::NAME));
+        let mut select = selects.next().unwrap();
+        for other in selects {
+            select.union(UnionType::All, other);
+        }
+        insert.columns(names.iter().copied());
+        insert.select_from(select).unwrap();
+        insert.returning_col(Alias::new(T::ID));
+        let (sql, values) = insert.build_rusqlite(SqliteQueryBuilder);
+
+        let mut statement = prepare_cached(&self.transaction, &sql);
+        let rows = statement
+            .query_map(&*values.as_params(), |row| {
+                Ok(TableRow::<'_, T>::from_sql(row.get_ref(T::ID)?)?)
+            })
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>();
+        match rows {
+            Ok(mut rows) => {
+                // the order of `RETURNING` is not specified, but new rows get increasing ids
+                rows.sort_by_key(|row| row.inner.idx);
+                Some(rows)
+            }
+            Err(rusqlite::Error::SqliteFailure(kind, Some(_val)))
+                if kind.code == ErrorCode::ConstraintViolation =>
+            {
+                None
+            }
+            Err(err) => Err(err).unwrap(),
+        }
+    }
+
+    /// This is a convenience function to make using [TransactionMut::try_insert_batch]
+    /// easier for tables without unique constraints.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Warehouse { name: String, tax: f64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// let names: Vec<_> = (0..1000).map(|i| format!("warehouse {i}")).collect();
+    /// let warehouses = txn.insert_batch(names.iter().map(|name| Warehouse { name, tax: 0.1 }));
+    /// assert_eq!(warehouses.len(), 1000);
+    /// assert_eq!(txn.query_one(warehouses[5].name()), "warehouse 5");
+    /// ```
+    pub fn insert_batch<T: Table<Schema = S>>(
+        &mut self,
+        vals: impl IntoIterator<Item = impl Writable<'t, T = T, Conflict = Infallible, Schema = S>>,
+    ) -> Vec<TableRow<'t, T>> {
+        let Ok(rows) = self.try_insert_batch(vals);
+        rows
+    }
+
     /// Try updating a row in the database to have new column values.
     ///
     /// Updating can fail just like [TransactionMut::try_insert] because of unique constraint conflicts.
@@ -247,6 +697,17 @@ impl<'t, S: 'static> TransactionMut<'t, S> {
         row: impl IntoColumn<'t, S, Typ = T>,
         val: impl Writable<'t, T = T, Conflict = C, Schema = S>,
     ) -> Result<(), C> {
+        self.try_update_private(row, val, ().into_dummy())
+            .map(|_| ())
+    }
+
+    /// Returns the result of `dummy` for the updated row, which is [None] if the row does not exist.
+    fn try_update_private<T: Table<Schema = S>, C, I: DummyImpl>(
+        &mut self,
+        row: impl IntoColumn<'t, S, Typ = T>,
+        val: impl Writable<'t, T = T, Conflict = C, Schema = S>,
+        dummy: Dummy<'t, 't, S, I>,
+    ) -> Result<Option<I::Out>, C> {
         let ast = MySelect::default();
 
         let reader = Reader {
@@ -256,39 +717,51 @@ impl<'t, S: 'static> TransactionMut<'t, S> {
         };
         val.read(reader);
 
-        let select = ast.simple();
-        let (query, args) = select.build_rusqlite(SqliteQueryBuilder);
-        let mut stmt = self.transaction.prepare_cached(&query).unwrap();
-
         let row_id = self.query_one(row.into_column()).inner.idx;
         let mut update = UpdateStatement::new()
             .table(Alias::new(T::NAME))
             .cond_where(Expr::val(row_id).equals(Alias::new(T::ID)))
             .to_owned();
 
-        stmt.query_row(&*args.as_params(), |row| {
-            for (_, field) in ast.select.iter() {
-                let Field::Str(name) = field else { panic!() };
-
-                let val = match row.get_unwrap::<&str, rusqlite::types::Value>(*name) {
-                    rusqlite::types::Value::Null => Value::BigInt(None),
-                    rusqlite::types::Value::Integer(x) => Value::BigInt(Some(x)),
-                    rusqlite::types::Value::Real(x) => Value::Double(Some(x)),
-                    rusqlite::types::Value::Text(x) => Value::String(Some(Box::new(x))),
-                    rusqlite::types::Value::Blob(_) => todo!(),
-                };
-                update.value(*field, Expr::val(val));
-            }
-            Ok(())
-        })
-        .unwrap();
+        if ast.select.is_empty() {
+            // all columns are kept as they are, but we still want the number of matched rows
+            update.value(Alias::new(T::ID), Expr::col(Alias::new(T::ID)));
+        } else {
+            let select = ast.simple();
+            let (query, args) = select.build_rusqlite(SqliteQueryBuilder);
+            let mut stmt = prepare_cached(&self.transaction, &query);
+
+            stmt.query_row(&*args.as_params(), |row| {
+                for (_, field) in ast.select.iter() {
+                    let Field::Str(name) = field else {
+                        unreachable!(
+                            "the fields of a table dummy are always named after the column"
+                        )
+                    };
+
+                    let val = match row.get_unwrap::<&str, rusqlite::types::Value>(*name) {
+                        rusqlite::types::Value::Null => Value::BigInt(None),
+                        rusqlite::types::Value::Integer(x) => Value::BigInt(Some(x)),
+                        rusqlite::types::Value::Real(x) => Value::Double(Some(x)),
+                        rusqlite::types::Value::Text(x) => Value::String(Some(Box::new(x))),
+                        rusqlite::types::Value::Blob(x) => Value::Bytes(Some(Box::new(x))),
+                    };
+                    update.value(*field, Expr::val(val));
+                }
+                Ok(())
+            })
+            .unwrap();
+        }
+
+        let (returning, fields, mut prepared) = returning(dummy);
+        update.returning(ReturningClause::Exprs(returning));
 
         let (query, args) = update.build_rusqlite(SqliteQueryBuilder);
 
-        let mut stmt = self.transaction.prepare_cached(&query).unwrap();
-        match stmt.execute(&*args.as_params()) {
-            Ok(1) => Ok(()),
-            Ok(n) => panic!("unexpected number of updates: {n}"),
+        let mut stmt = prepare_cached(&self.transaction, &query);
+        let mut rows = stmt.query(&*args.as_params()).unwrap();
+        match rows.next() {
+            Ok(row) => Ok(row.map(|row| prepared.call(Row::new(row, &fields)))),
             Err(rusqlite::Error::SqliteFailure(kind, Some(_val)))
                 if kind.code == ErrorCode::ConstraintViolation =>
             {
@@ -310,6 +783,50 @@ impl<'t, S: 'static> TransactionMut<'t, S> {
         let Ok(()) = self.try_update(row, val);
     }
 
+    /// Same as [TransactionMut::try_update], but also returns the new values of the row.
+    ///
+    /// The `dummy` is retrieved with `UPDATE ... RETURNING`, so it sees the updated values.
+    /// This is useful for columns that are computed from other columns, like generated columns.
+    pub fn try_update_returning<T: Table<Schema = S>, C, O>(
+        &mut self,
+        row: impl IntoColumn<'t, S, Typ = T>,
+        val: impl Writable<'t, T = T, Conflict = C, Schema = S>,
+        dummy: impl IntoDummy<'t, 't, S, Out = O>,
+    ) -> Result<O, C> {
+        let out = self.try_update_private(row, val, dummy.into_dummy())?;
+        Ok(out.expect("rows can not be deleted in a TransactionMut"))
+    }
+
+    /// This is a convenience function to use [TransactionMut::try_update_returning] on tables without
+    /// unique constraints.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Account { name: String, cents: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// let alice = txn.insert(Account { name: "Alice", cents: 100 });
+    /// let cents = txn.update_returning(
+    ///     alice,
+    ///     Account { name: alice.name(), cents: alice.cents().add(50) },
+    ///     alice.cents(),
+    /// );
+    /// assert_eq!(cents, 150);
+    /// ```
+    pub fn update_returning<T: Table<Schema = S>, O>(
+        &mut self,
+        row: impl IntoColumn<'t, S, Typ = T>,
+        val: impl Writable<'t, T = T, Conflict = Infallible, Schema = S>,
+        dummy: impl IntoDummy<'t, 't, S, Out = O>,
+    ) -> O {
+        let Ok(out) = self.try_update_returning(row, val, dummy);
+        out
+    }
+
     /// This is a convenience function to use [TransactionMut::try_update] on tables with
     /// exactly one unique constraint.
     ///
@@ -331,6 +848,279 @@ impl<'t, S: 'static> TransactionMut<'t, S> {
         }
     }
 
+    /// Insert a new row or update the existing row if it is in conflict with the new row.
+    ///
+    /// This is only available for tables with exactly one unique constraint.
+    /// The closure receives the conflicting row and returns its new column values,
+    /// which can depend on the old values of the row.
+    /// Everything is done with a single `INSERT ... ON CONFLICT DO UPDATE` statement.
+    /// Returns a reference to the inserted or updated row.
+    ///
+    /// # Panics
+    /// If the updated row is in conflict with yet another row.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Visit { #[unique] page: String, count: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// for page in ["home", "about", "home"] {
+    ///     txn.insert_or_update(Visit { page, count: 1 }, |visit| Visit {
+    ///         count: visit.count().add(1),
+    ///         ..Visit::dummy(visit)
+    ///     });
+    /// }
+    /// let home = txn.query_one(Visit::unique("home")).unwrap();
+    /// assert_eq!(txn.query_one(home.count()), 2);
+    /// ```
+    pub fn insert_or_update<T: Table<Schema = S>>(
+        &mut self,
+        val: impl Writable<'t, T = T, Conflict = TableRow<'t, T>, Schema = S>,
+        f: impl for<'inner> FnOnce(Column<'inner, S, T>) -> T::Dummy<'inner>,
+    ) -> TableRow<'t, T>
+    where
+        for<'inner> T::Dummy<'inner>: Writable<'inner, T = T, Schema = S>,
+    {
+        let ast = MySelect::default();
+        let reader = Reader {
+            ast: &ast,
+            _p: PhantomData,
+            _p2: PhantomData,
+        };
+        val.read(reader);
+
+        let mut insert = InsertStatement::new();
+        insert.into_table(Alias::new(T::NAME));
+        if ast.select.is_empty() {
+            // all columns use their default value
+            insert.or_default_values();
+        } else {
+            let names = ast.select.iter().map(|(_field, name)| *name);
+            insert.columns(names);
+            let mut select = ast.build_select(false);
+            // the `WHERE` clause is required to make the `ON CONFLICT` clause unambiguous
+            select.and_where(Expr::val(true).into());
+            insert.select_from(select).unwrap();
+        }
+        let (insert, mut values) = insert.build_rusqlite(SqliteQueryBuilder);
+
+        let mut rows = Rows {
+            phantom: PhantomData,
+            ast: MySelect::default(),
+            _p: PhantomData,
+        };
+        let row = T::join(&mut rows);
+        // the conflicting row is available using the name of the table
+        let id = row.inner.build_expr(rows.ast.builder());
+        let existing = Expr::col((Alias::new(T::NAME), Alias::new(T::ID)));
+        rows.ast.filters.push(Box::new(id.eq(existing)));
+        let new = f(row);
+
+        let reader = Reader {
+            ast: &rows.ast,
+            _p: PhantomData,
+            _p2: PhantomData,
+        };
+        new.read(reader);
+
+        let mut names = vec![];
+        for (_, field) in rows.ast.select.iter() {
+            let Field::Str(name) = field else {
+                unreachable!("the fields of a table dummy are always named after the column")
+            };
+            names.push(format!("\"{name}\""));
+        }
+        let (select, update_values) = rows
+            .ast
+            .build_select(false)
+            .build_rusqlite(SqliteQueryBuilder);
+        values.0.extend(update_values.0);
+        let update = format!("({}) = ({select})", names.join(", "));
+        let id = T::ID;
+        let sql = format!("{insert} ON CONFLICT DO UPDATE SET {update} RETURNING \"{id}\"");
+
+        let mut statement = prepare_cached(&self.transaction, &sql);
+        let mut res = statement
+            .query_map(&*values.as_params(), |row| {
+                Ok(TableRow::<'_, T>::from_sql(row.get_ref(T::ID)?)?)
+            })
+            .unwrap();
+        res.next().unwrap().unwrap()
+    }
+
+    /// Insert a new row for every row of a query, using a single `INSERT INTO ... SELECT` statement.
+    ///
+    /// The closure receives the [Rows] of the query and returns the columns of the new row.
+    /// The new row is specified with the [Table::Dummy] type, so every column needs to be a [crate::Column].
+    /// Use [IntoColumn::into_column] to turn constant values into columns.
+    ///
+    /// This is only available for tables without unique constraints.
+    /// Returns the number of inserted rows.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Invoice { customer: String, total: f64, paid: bool },
+    /// #     Archive { customer: String, total: f64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// # txn.insert(Invoice { customer: "Alice", total: 10., paid: true });
+    /// # txn.insert(Invoice { customer: "Bob", total: 5., paid: false });
+    /// let archived = txn.insert_select::<Archive>(|rows| {
+    ///     let invoice = Invoice::join(rows);
+    ///     rows.filter(invoice.paid());
+    ///     Archive {
+    ///         customer: invoice.customer(),
+    ///         total: invoice.total(),
+    ///     }
+    /// });
+    /// assert_eq!(archived, 1);
+    /// ```
+    pub fn insert_select<T: Table<Schema = S>>(
+        &mut self,
+        f: impl for<'inner> FnOnce(&mut Rows<'inner, S>) -> T::Dummy<'inner>,
+    ) -> usize
+    where
+        for<'inner> T::Dummy<'inner>: Writable<'inner, T = T, Conflict = Infallible, Schema = S>,
+    {
+        let mut rows = Rows {
+            phantom: PhantomData,
+            ast: MySelect::default(),
+            _p: PhantomData,
+        };
+        let val = f(&mut rows);
+
+        let reader = Reader {
+            ast: &rows.ast,
+            _p: PhantomData,
+            _p2: PhantomData,
+        };
+        val.read(reader);
+
+        let select = rows.ast.simple();
+
+        let mut insert = InsertStatement::new();
+        insert.into_table(Alias::new(T::NAME));
+        let names = rows.ast.select.iter().map(|(_field, name)| *name);
+        insert.columns(names);
+        insert.select_from(select).unwrap();
+
+        let (sql, values) = insert.build_rusqlite(SqliteQueryBuilder);
+        let mut statement = prepare_cached(&self.transaction, &sql);
+        statement.execute(&*values.as_params()).unwrap()
+    }
+
+    /// Try updating all rows of a table that are returned by a query.
+    ///
+    /// The closure receives the [Rows] of the query and a row of the table that is being updated.
+    /// It can filter the rows and returns the new column values, which can depend on the row.
+    /// Everything is updated with a single `UPDATE ... FROM` statement.
+    ///
+    /// If the query returns the same row of the table multiple times, for example because of a join,
+    /// then that row is updated only once with the values of an arbitrary one of the results.
+    ///
+    /// If any of the new rows is in conflict with an existing row because of a unique constraint,
+    /// then nothing is updated and [Err] is returned.
+    /// Otherwise this returns the number of updated rows.
+    pub fn try_update_where<T: Table<Schema = S>>(
+        &mut self,
+        f: impl for<'inner> FnOnce(&mut Rows<'inner, S>, Column<'inner, S, T>) -> T::Dummy<'inner>,
+    ) -> Result<usize, UpdateConflict>
+    where
+        for<'inner> T::Dummy<'inner>: Writable<'inner, T = T, Schema = S>,
+    {
+        let mut rows = Rows {
+            phantom: PhantomData,
+            ast: MySelect::default(),
+            _p: PhantomData,
+        };
+        let row = T::join(&mut rows);
+        let val = f(&mut rows, row.clone());
+
+        let reader = Reader {
+            ast: &rows.ast,
+            _p: PhantomData,
+            _p2: PhantomData,
+        };
+        val.read(reader);
+
+        let mut sets = vec![];
+        for (_, field) in rows.ast.select.iter() {
+            let Field::Str(name) = field else {
+                unreachable!("the fields of a table dummy are always named after the column")
+            };
+            sets.push(format!("\"{name}\" = \"_update\".\"{name}\""));
+        }
+        if sets.is_empty() {
+            // the table has no columns, so there is nothing to update
+            return Ok(0);
+        }
+
+        let id = row.inner.build_expr(rows.ast.builder());
+        rows.ast.select.push(Box::new((id, Field::Str(T::ID))));
+        let select = rows.ast.build_select(false);
+
+        let (select, values) = select.build_rusqlite(SqliteQueryBuilder);
+        let (table, id) = (T::NAME, T::ID);
+        let sql = format!(
+            "UPDATE \"{table}\" SET {} FROM ({select}) AS \"_update\" \
+            WHERE \"{table}\".\"{id}\" = \"_update\".\"{id}\"",
+            sets.join(", ")
+        );
+
+        let mut stmt = prepare_cached(&self.transaction, &sql);
+        match stmt.execute(&*values.as_params()) {
+            Ok(n) => Ok(n),
+            Err(rusqlite::Error::SqliteFailure(kind, Some(message)))
+                if kind.code == ErrorCode::ConstraintViolation =>
+            {
+                Err(UpdateConflict { message })
+            }
+            Err(err) => Err(err).unwrap(),
+        }
+    }
+
+    /// This is a convenience function to use [TransactionMut::try_update_where] on tables without
+    /// unique constraints.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, IntoColumn, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Shipment { district: i64, carrier: Option<i64> },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// # for district in [1, 1, 2] {
+    /// #     txn.insert(Shipment { district, carrier: None::<i64> });
+    /// # }
+    /// let updated = txn.update_where::<Shipment>(|rows, shipment| {
+    ///     rows.filter(shipment.district().eq(1));
+    ///     Shipment {
+    ///         carrier: Some(7).into_column(),
+    ///         ..Shipment::dummy(shipment)
+    ///     }
+    /// });
+    /// assert_eq!(updated, 2);
+    /// ```
+    pub fn update_where<T: Table<Schema = S>>(
+        &mut self,
+        f: impl for<'inner> FnOnce(&mut Rows<'inner, S>, Column<'inner, S, T>) -> T::Dummy<'inner>,
+    ) -> usize
+    where
+        for<'inner> T::Dummy<'inner>: Writable<'inner, T = T, Conflict = Infallible, Schema = S>,
+    {
+        self.try_update_where(f).unwrap()
+    }
+
     /// Make the changes made in this [TransactionMut] permanent.
     ///
     /// If the [TransactionMut] is dropped without calling this function, then the changes are rolled back.
@@ -338,11 +1128,64 @@ impl<'t, S: 'static> TransactionMut<'t, S> {
         self.inner.transaction.commit().unwrap();
     }
 
+    /// Undo the changes made in this [TransactionMut].
+    ///
+    /// This is the same as dropping the [TransactionMut], but makes the intent explicit.
+    /// The [TransactionMut] can be passed around before deciding to call [TransactionMut::commit] or this function.
+    /// Note that other [TransactionMut]s have to wait until that decision is made,
+    /// so waiting on something that needs another [TransactionMut] will deadlock.
+    pub fn rollback(self) {
+        self.inner.transaction.rollback().unwrap();
+    }
+
     pub fn downgrade(self) -> TransactionWeak<'t, S> {
         TransactionWeak { inner: self }
     }
 }
 
+/// The row that is inserted by an `INSERT ... RETURNING` statement.
+///
+/// It can only be used in the `RETURNING` clause of that statement.
+struct NewRow<T>(PhantomData<T>);
+
+impl<T: Table> Typed for NewRow<T> {
+    type Typ = T;
+    fn build_expr(&self, _: ValueBuilder) -> SimpleExpr {
+        Expr::col((Alias::new(T::NAME), Alias::new(T::ID))).into()
+    }
+}
+
+/// Get the `RETURNING` clause for a [Dummy] and the fields to read it.
+fn returning<I: DummyImpl>(
+    dummy: Dummy<'_, '_, impl Sized, I>,
+) -> (Vec<SimpleExpr>, Vec<Field>, I::Prepared) {
+    let mut cacher = Cacher::new();
+    let prepared = dummy.inner.prepare(&mut cacher);
+    let ast = MySelect::default();
+    let fields = ast.cache(cacher.columns);
+    (ast.build_returning(), fields, prepared)
+}
+
+/// The default maximum number of terms in a compound select.
+const MAX_COMPOUND_SELECT: usize = 500;
+/// The default maximum number of parameters in a statement since sqlite 3.32.0.
+const MAX_VARIABLE_NUMBER: usize = 32766;
+
+/// The error returned by [TransactionMut::try_update_where] when an updated row would be
+/// in conflict with another row because of a unique constraint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateConflict {
+    message: String,
+}
+
+impl std::fmt::Display for UpdateConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for UpdateConflict {}
+
 /// This is the weak version of [TransactionMut].
 ///
 /// The reason that it is called `weak` is because [TransactionWeak] can not guarantee
@@ -400,6 +1243,114 @@ impl<'t, S: 'static> TransactionWeak<'t, S> {
         self.try_delete(val).unwrap()
     }
 
+    /// Try updating a row that might have been deleted.
+    ///
+    /// This works the same as [TransactionMut::try_update], but it returns the number of updated rows.
+    /// The number is `0` if the row was deleted previously in this transaction and `1` otherwise.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     #[no_reference]
+    /// #     Draft { text: String, version: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// let draft = txn.insert(Draft { text: "hello", version: 1 });
+    /// let mut txn = txn.downgrade();
+    ///
+    /// let new = Draft { text: "hello world", version: draft.version().add(1) };
+    /// assert_eq!(txn.try_update_count(draft, new), Ok(1));
+    ///
+    /// txn.delete(draft);
+    /// let new = Draft { text: "bye", version: draft.version().add(1) };
+    /// assert_eq!(txn.try_update_count(draft, new), Ok(0));
+    /// ```
+    pub fn try_update_count<T: Table<Schema = S>, C>(
+        &mut self,
+        row: TableRow<'t, T>,
+        val: impl Writable<'t, T = T, Conflict = C, Schema = S>,
+    ) -> Result<usize, C> {
+        let out = self.inner.try_update_private(row, val, ().into_dummy())?;
+        Ok(out.map_or(0, |()| 1))
+    }
+
+    /// This is a convenience function to use [TransactionWeak::try_update_count] on tables without
+    /// unique constraints.
+    pub fn update_count<T: Table<Schema = S>>(
+        &mut self,
+        row: TableRow<'t, T>,
+        val: impl Writable<'t, T = T, Conflict = Infallible, Schema = S>,
+    ) -> usize {
+        let Ok(n) = self.try_update_count(row, val);
+        n
+    }
+
+    /// Try to delete all rows that are returned by a query.
+    ///
+    /// The closure receives the [Rows] of the query and returns the rows that should be deleted.
+    /// Everything is deleted with a single `DELETE ... WHERE id IN (SELECT ...)` statement.
+    ///
+    /// Just like [TransactionWeak::try_delete], this will return an [Err] if there is a row that
+    /// references one of the rows that are being deleted. In that case nothing is deleted.
+    /// Otherwise it returns the number of deleted rows.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     #[no_reference]
+    /// #     NewOrder { district: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// # for district in [1, 1, 2] {
+    /// #     txn.insert(NewOrder { district });
+    /// # }
+    /// let mut txn = txn.downgrade();
+    /// let deleted = txn.delete_where(|rows| {
+    ///     let new_order = NewOrder::join(rows);
+    ///     rows.filter(new_order.district().eq(1));
+    ///     new_order
+    /// });
+    /// assert_eq!(deleted, Ok(2));
+    /// ```
+    pub fn delete_where<T: Table<Schema = S>>(
+        &mut self,
+        f: impl for<'inner> FnOnce(&mut Rows<'inner, S>) -> Column<'inner, S, T>,
+    ) -> Result<usize, T::Referer> {
+        let mut rows = Rows {
+            phantom: PhantomData,
+            ast: MySelect::default(),
+            _p: PhantomData,
+        };
+        let val = f(&mut rows);
+        rows.ast.cache([val.inner.erase()]);
+        let select = rows.ast.build_select(false);
+
+        let stmt = DeleteStatement::new()
+            .from_table(Alias::new(T::NAME))
+            .cond_where(Expr::col(Alias::new(T::ID)).in_subquery(select))
+            .to_owned();
+
+        let (query, args) = stmt.build_rusqlite(SqliteQueryBuilder);
+        let mut stmt = prepare_cached(&self.inner.transaction, &query);
+
+        match stmt.execute(&*args.as_params()) {
+            Ok(n) => Ok(n),
+            Err(rusqlite::Error::SqliteFailure(kind, Some(_val)))
+                if kind.code == ErrorCode::ConstraintViolation =>
+            {
+                // Some foreign key constraint got violated
+                Err(T::get_referer_unchecked())
+            }
+            Err(err) => Err(err).unwrap(),
+        }
+    }
+
     /// This allows you to do anything you want with the internal [rusqlite::Transaction]
     ///
     /// The specific version of rusqlite used is not stable. This means the [rusqlite]
@@ -414,4 +1365,11 @@ impl<'t, S: 'static> TransactionWeak<'t, S> {
     pub fn commit(self) {
         self.inner.commit();
     }
+
+    /// Undo the changes made in this [TransactionWeak].
+    ///
+    /// This is the same as dropping the [TransactionWeak], but makes the intent explicit.
+    pub fn rollback(self) {
+        self.inner.rollback();
+    }
 }
@@ -1,10 +1,19 @@
+#[cfg(feature = "chrono")]
+pub mod datetime;
+#[cfg(feature = "json")]
+pub mod json;
 pub mod operations;
 pub mod optional;
 pub mod trivial;
+pub mod window;
 
-use std::{marker::PhantomData, ops::Deref, rc::Rc};
+use std::{marker::PhantomData, ops::Deref, rc::Rc, time::Duration};
 
-use operations::{Add, And, AsFloat, Eq, Glob, IsNotNull, Like, Lt, Not, Or, UnwrapOr};
+use operations::{
+    Add, And, AsFloat, Assume, CallFn, Case, CastInt, Ceil, Collate, Concat, ConcatWs, Eq, Floor,
+    Glob, In, InQuery, IsNotNull, Like, Lt, Not, Or, ParseFloat, ParseInt, Pow, RawSql, Round,
+    SqliteTypeof, Sqrt, UnwrapOr,
+};
 use ref_cast::RefCast;
 use sea_query::{Alias, Expr, Nullable, SelectStatement, SimpleExpr};
 use trivial::{FromColumn, Trivial};
@@ -15,7 +24,7 @@ use crate::{
     db::{TableRow, TableRowInner},
     hash,
     migrate::NoTable,
-    Table,
+    Rows, Table,
 };
 
 #[derive(Clone, Copy)]
@@ -90,9 +99,23 @@ impl EqTyp for String {}
 impl EqTyp for i64 {}
 impl EqTyp for f64 {}
 impl EqTyp for bool {}
+impl EqTyp for Vec<u8> {}
+#[cfg(feature = "uuid")]
+impl EqTyp for uuid::Uuid {}
 #[diagnostic::do_not_recommend]
 impl<T: Table> EqTyp for T {}
 
+#[diagnostic::on_unimplemented(
+    message = "Columns with type `{Self}` can not be ordered",
+    note = "`OrdTyp` is implemented for numbers, strings, blobs and datetimes"
+)]
+pub trait OrdTyp {}
+
+impl OrdTyp for String {}
+impl OrdTyp for i64 {}
+impl OrdTyp for f64 {}
+impl OrdTyp for Vec<u8> {}
+
 /// Typ does not depend on scope, so it gets its own trait
 pub trait Typed {
     type Typ;
@@ -112,6 +135,8 @@ pub(crate) trait Private {}
 
 /// Trait for all values that can be used as columns in queries.
 ///
+/// Integers are always [i64] in queries, use [i64::from] to convert smaller integer types.
+///
 /// You can not (yet) implement this trait yourself!
 pub trait IntoColumn<'column, S>: Private + Clone {
     /// The type of the column.
@@ -146,6 +171,61 @@ impl<'column, S, T: EqTyp + 'static> Column<'column, S, T> {
     pub fn eq(&self, rhs: impl IntoColumn<'column, S, Typ = T>) -> Column<'column, S, bool> {
         Column::new(Eq(self.inner.clone(), rhs.into_column().inner))
     }
+
+    /// Check whether the column is equal to any of the values.
+    ///
+    /// This is the `IN` operator of sqlite. An empty list of values results in `false`.
+    pub fn in_values(
+        &self,
+        values: impl IntoIterator<Item = impl IntoColumn<'column, S, Typ = T>>,
+    ) -> Column<'column, S, bool> {
+        let values = values.into_iter().map(|x| x.into_column().inner);
+        Column::new(In(self.inner.clone(), values.collect()))
+    }
+
+    /// Check whether the column is equal to any of the results of a sub-query.
+    ///
+    /// This is the `IN (SELECT ...)` operator of sqlite.
+    /// The sub-query can not use columns of the outer query, use [crate::exists] for that instead.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Customer { name: String },
+    /// #     Invoice { customer: Customer, total: f64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// # let alice = txn.insert(Customer { name: "Alice" });
+    /// # txn.insert(Customer { name: "Bob" });
+    /// # txn.insert(Invoice { customer: alice, total: 10. });
+    /// let with_invoice = txn.query(|rows| {
+    ///     let customer = Customer::join(rows);
+    ///     rows.filter(customer.in_query(|rows| {
+    ///         let invoice = Invoice::join(rows);
+    ///         invoice.customer()
+    ///     }));
+    ///     rows.into_vec(customer.name())
+    /// });
+    /// assert_eq!(with_invoice, ["Alice"]);
+    /// ```
+    pub fn in_query(
+        &self,
+        f: impl for<'inner> FnOnce(&mut Rows<'inner, S>) -> Column<'inner, S, T>,
+    ) -> Column<'column, S, bool> {
+        let mut rows = Rows {
+            phantom: PhantomData,
+            ast: MySelect::default(),
+            _p: PhantomData,
+        };
+        let val = f(&mut rows).inner;
+        let val = val.build_expr(rows.ast.builder());
+        let alias = rows.ast.scope.new_alias();
+        rows.ast.filter_on.push(Box::new((val, alias)));
+        Column::new(InQuery(self.inner.clone(), rows.ast.build_filter_on()))
+    }
 }
 
 impl<'column, S> Column<'column, S, bool> {
@@ -174,12 +254,86 @@ impl<'column, S, Typ: 'static> Column<'column, S, Option<Typ>> {
         Column::new(UnwrapOr(self.inner.clone(), rhs.into_column().inner))
     }
 
+    /// Use the first column if it is [Some], otherwise use the second optional column.
+    ///
+    /// This is the same as [Column::unwrap_or], except that the fallback can also be [None].
+    /// It is useful to fall back on an [crate::aggregate], which is optional when there are no rows.
+    /// ```
+    /// # use rust_query::{aggregate, migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Reading { sensor: String, value: Option<f64> },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// txn.insert(Reading { sensor: "a", value: Some(1.) });
+    /// txn.insert(Reading { sensor: "a", value: Some(3.) });
+    /// txn.insert(Reading { sensor: "a", value: None::<f64> });
+    /// // replace missing values with the average of the sensor
+    /// let values = txn.query(|rows| {
+    ///     let reading = Reading::join(rows);
+    ///     let average = aggregate(|rows| {
+    ///         let other = Reading::join(rows);
+    ///         rows.filter_on(other.sensor(), reading.sensor());
+    ///         let value = rows.filter_some(other.value());
+    ///         rows.avg(value)
+    ///     });
+    ///     rows.into_vec(reading.value().or(average))
+    /// });
+    /// assert_eq!(values, [Some(1.), Some(2.), Some(3.)]);
+    /// ```
+    pub fn or(
+        &self,
+        rhs: impl IntoColumn<'column, S, Typ = Option<Typ>>,
+    ) -> Column<'column, S, Option<Typ>>
+    where
+        Self: IntoColumn<'column, S, Typ = Option<Typ>>,
+    {
+        Column::new(UnwrapOr(self.inner.clone(), rhs.into_column().inner))
+    }
+
     /// Check that the column is [Some].
     pub fn is_some(&self) -> Column<'column, S, bool> {
         Column::new(IsNotNull(self.inner.clone()))
     }
 }
 
+impl<'column, S, Typ: 'static> Column<'column, S, Option<Option<Typ>>> {
+    /// Remove one level of [Option] from the column.
+    ///
+    /// Sqlite only has one `NULL` value, so this does not change the column value.
+    /// This is useful when the column of an [crate::optional] combinator is itself optional.
+    pub fn flatten(&self) -> Column<'column, S, Option<Typ>> {
+        Column::new(Assume(self.inner.clone()))
+    }
+}
+
+impl<'column, S, T: 'static> Column<'column, S, T> {
+    /// Get the type that sqlite uses to store the value, using the sqlite `typeof` function.
+    ///
+    /// The result is one of `"integer"`, `"real"`, `"text"`, `"blob"` or `"null"`.
+    /// This is mostly useful for debugging.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, IntoColumn, LocalClient};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Item { count: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// let item = txn.insert(Item { count: 3 });
+    /// assert_eq!(txn.query_one(item.count().sqlite_typeof()), "integer");
+    /// assert_eq!(txn.query_one(None::<i64>.into_column().sqlite_typeof()), "null");
+    /// ```
+    pub fn sqlite_typeof(&self) -> Column<'column, S, String> {
+        Column::new(SqliteTypeof(self.inner.clone()))
+    }
+}
+
 impl<'column, S> Column<'column, S, i64> {
     /// Convert the [i64] column to [f64] type.
     pub fn as_float(&self) -> Column<'column, S, f64> {
@@ -187,6 +341,82 @@ impl<'column, S> Column<'column, S, i64> {
     }
 }
 
+impl<'column, S> Column<'column, S, f64> {
+    /// Round the [f64] column to the specified number of digits after the decimal point.
+    ///
+    /// Values exactly halfway between two options are rounded away from zero.
+    /// A negative number of digits is treated the same as zero.
+    pub fn round(&self, digits: i64) -> Column<'column, S, f64> {
+        Column::new(Round(self.inner.clone(), digits))
+    }
+
+    /// Round the [f64] column down to the nearest integer.
+    ///
+    /// This does not require sqlite to be compiled with `SQLITE_ENABLE_MATH_FUNCTIONS`,
+    /// it is implemented in terms of `CAST(x AS INTEGER)` instead.
+    pub fn floor(&self) -> Column<'column, S, f64> {
+        Column::new(Floor(self.inner.clone()))
+    }
+
+    /// Round the [f64] column up to the nearest integer.
+    ///
+    /// This does not require sqlite to be compiled with `SQLITE_ENABLE_MATH_FUNCTIONS`,
+    /// it is implemented in terms of `CAST(x AS INTEGER)` instead.
+    pub fn ceil(&self) -> Column<'column, S, f64> {
+        Column::new(Ceil(self.inner.clone()))
+    }
+
+    /// Convert the [f64] column to [i64] with `CAST(x AS INTEGER)`.
+    ///
+    /// The fractional part is truncated, so the value is rounded towards zero.
+    /// Values that are too large for an [i64] are clamped to [i64::MIN] or [i64::MAX].
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, IntoColumn, LocalClient};
+    /// # #[schema]
+    /// # enum Schema {}
+    /// # use v0::*;
+    /// # fn main() {
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let txn = client.transaction(&database);
+    /// let cast = |x: f64| txn.query_one(x.into_column().cast_int());
+    /// assert_eq!(cast(2.7), 2);
+    /// assert_eq!(cast(-2.7), -2);
+    /// assert_eq!(cast(1e300), i64::MAX);
+    /// # }
+    /// ```
+    pub fn cast_int(&self) -> Column<'column, S, i64> {
+        Column::new(CastInt(self.inner.clone()))
+    }
+
+    /// Compute the square root of the [f64] column.
+    ///
+    /// The result is [None] if the input is negative.
+    ///
+    /// This requires sqlite to be compiled with `SQLITE_ENABLE_MATH_FUNCTIONS`,
+    /// which is not the case for the `bundled` feature.
+    /// This is checked when the database is opened, executing a query that uses
+    /// this function will panic with a clear message if it is not available.
+    pub fn sqrt(&self) -> Column<'column, S, Option<f64>> {
+        Column::new(Sqrt(self.inner.clone()))
+    }
+
+    /// Raise the [f64] column to the power of `exp`.
+    ///
+    /// The result is [None] if it is not a real number, e.g. for `pow(-8, 1/3)`.
+    ///
+    /// This requires sqlite to be compiled with `SQLITE_ENABLE_MATH_FUNCTIONS`,
+    /// which is not the case for the `bundled` feature.
+    /// This is checked when the database is opened, executing a query that uses
+    /// this function will panic with a clear message if it is not available.
+    pub fn pow(
+        &self,
+        exp: impl IntoColumn<'column, S, Typ = f64>,
+    ) -> Column<'column, S, Option<f64>> {
+        Column::new(Pow(self.inner.clone(), exp.into_column().inner))
+    }
+}
+
 impl<'column, S> Column<'column, S, String> {
     /// Check if the column starts with the string pattern.
     ///
@@ -218,13 +448,62 @@ impl<'column, S> Column<'column, S, String> {
         ))
     }
 
+    /// Concatenate two strings with the `||` operator.
+    pub fn concat(
+        &self,
+        rhs: impl IntoColumn<'column, S, Typ = String>,
+    ) -> Column<'column, S, String> {
+        Column::new(Concat(self.inner.clone(), rhs.into_column().inner))
+    }
+
+    /// Check if the column starts with the string pattern, ignoring case.
+    ///
+    /// This uses [like](Self::like), so it is only **case-insensitive** for ASCII characters.
+    /// The pattern gets automatically escaped.
+    pub fn istarts_with(&self, pattern: impl AsRef<str>) -> Column<'column, S, bool> {
+        self.like(format!("{}%", escape_like(pattern)))
+    }
+
+    /// Check if the column ends with the string pattern, ignoring case.
+    ///
+    /// This uses [like](Self::like), so it is only **case-insensitive** for ASCII characters.
+    /// The pattern gets automatically escaped.
+    pub fn iends_with(&self, pattern: impl AsRef<str>) -> Column<'column, S, bool> {
+        self.like(format!("%{}", escape_like(pattern)))
+    }
+
+    /// Check if the column contains the string pattern, ignoring case.
+    ///
+    /// This uses [like](Self::like), so it is only **case-insensitive** for ASCII characters.
+    /// The pattern gets automatically escaped.
+    pub fn icontains(&self, pattern: impl AsRef<str>) -> Column<'column, S, bool> {
+        self.like(format!("%{}%", escape_like(pattern)))
+    }
+
     /// Check if the column matches the pattern [docs](https://www.sqlite.org/lang_expr.html#like).
     ///
     /// As noted in the docs, it is **case-insensitive** for ASCII characters. Other characters are case-sensitive.
     /// For creating patterns it uses `%` as a wildcard for any sequence of characters and `_` for any single character.
     /// Special characters should be escaped with `\`.
     pub fn like(&self, pattern: impl Into<String>) -> Column<'column, S, bool> {
-        Column::new(Like(self.inner.clone(), pattern.into()))
+        self.like_escape(pattern, '\\')
+    }
+
+    /// Same as [like](Self::like), but special characters are escaped with `escape` instead of `\`.
+    ///
+    /// This is useful for patterns that contain a `\` as a normal character.
+    /// Use the escape character before `%` or `_` to match them literally.
+    pub fn like_escape(
+        &self,
+        pattern: impl Into<String>,
+        escape: char,
+    ) -> Column<'column, S, bool> {
+        Column::new(Like(self.inner.clone(), pattern.into(), escape))
+    }
+
+    /// Check if the column does not match the pattern, this is the inverse of [like](Self::like).
+    pub fn not_like(&self, pattern: impl Into<String>) -> Column<'column, S, bool> {
+        self.like(pattern).not()
     }
 
     /// Check if the column matches the pattern [docs](https://www.sqlite.org/lang_expr.html#like).
@@ -235,8 +514,298 @@ impl<'column, S> Column<'column, S, String> {
     pub fn glob(&self, rhs: impl IntoColumn<'column, S, Typ = String>) -> Column<'column, S, bool> {
         Column::new(Glob(self.inner.clone(), rhs.into_column().inner))
     }
+
+    /// Convert the string to [i64] with `CAST(x AS INTEGER)`.
+    ///
+    /// This uses the longest prefix of the string that looks like an integer, after skipping leading whitespace.
+    /// The result is `0` if there is no such prefix.
+    /// Use [Column::parse_i64] to get [None] for strings that are not an integer.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, IntoColumn, LocalClient};
+    /// # #[schema]
+    /// # enum Schema {}
+    /// # use v0::*;
+    /// # fn main() {
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let txn = client.transaction(&database);
+    /// let cast = |x: &str| txn.query_one(x.into_column().cast_int());
+    /// assert_eq!(cast(" 042"), 42);
+    /// assert_eq!(cast("42 apples"), 42);
+    /// assert_eq!(cast("4.9"), 4);
+    /// assert_eq!(cast("apples"), 0);
+    /// # }
+    /// ```
+    pub fn cast_int(&self) -> Column<'column, S, i64> {
+        Column::new(CastInt(self.inner.clone()))
+    }
+
+    /// Parse the string as an integer, resulting in [None] if it is not a valid integer.
+    ///
+    /// The string is converted with `CAST(x AS INTEGER)`, which uses the longest prefix
+    /// that looks like an integer and ignores the rest of the string.
+    /// To reject such partial matches, the result is converted back to a string and compared with the input.
+    /// This means that only integers in canonical form are accepted:
+    /// leading `+`, leading zeros and whitespace all result in [None].
+    ///
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, IntoColumn, LocalClient};
+    /// # #[schema]
+    /// # enum Schema {}
+    /// # use v0::*;
+    /// # fn main() {
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let txn = client.transaction(&database);
+    /// let parse = |x: &str| txn.query_one(x.into_column().parse_i64());
+    /// assert_eq!(parse("-42"), Some(-42));
+    /// assert_eq!(parse("042"), None);
+    /// assert_eq!(parse("42 apples"), None);
+    /// assert_eq!(parse("4.2"), None);
+    /// # }
+    /// ```
+    pub fn parse_i64(&self) -> Column<'column, S, Option<i64>> {
+        Column::new(ParseInt(self.inner.clone()))
+    }
+
+    /// Parse the string as a floating point number, resulting in [None] if it is not a valid number.
+    ///
+    /// A string is considered valid if it is a number according to the [JSON](https://www.json.org/) syntax.
+    /// This means that leading `+`, leading zeros, and a leading or trailing `.` result in [None],
+    /// while an exponent such as `1e3` and surrounding whitespace are allowed.
+    /// The valid string is then converted with `CAST(x AS REAL)`.
+    ///
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, IntoColumn, LocalClient};
+    /// # #[schema]
+    /// # enum Schema {}
+    /// # use v0::*;
+    /// # fn main() {
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let txn = client.transaction(&database);
+    /// let parse = |x: &str| txn.query_one(x.into_column().parse_f64());
+    /// assert_eq!(parse("4.25"), Some(4.25));
+    /// assert_eq!(parse("-1e3"), Some(-1000.));
+    /// assert_eq!(parse("3"), Some(3.));
+    /// assert_eq!(parse(".5"), None);
+    /// assert_eq!(parse("4.25 apples"), None);
+    /// # }
+    /// ```
+    pub fn parse_f64(&self) -> Column<'column, S, Option<f64>> {
+        Column::new(ParseFloat(self.inner.clone()))
+    }
+
+    /// Use the collation `collation` when this string is compared or sorted.
+    ///
+    /// The built-in collations of sqlite are `BINARY` (the default), `NOCASE` and `RTRIM`.
+    /// `NOCASE` only ignores the case of ASCII characters.
+    /// The collation applies to [Column::eq], [Column::lt] and to sorting with [crate::args::Query::order_by_asc] etc.
+    /// It does not change the value of the string.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Customer { name: String },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// for name in ["bob", "Carol", "alice"] {
+    ///     txn.insert(Customer { name });
+    /// }
+    /// let names = txn.query(|rows| {
+    ///     let customer = Customer::join(rows);
+    ///     let name = customer.name().collate("NOCASE");
+    ///     rows.filter(name.eq("BOB").not());
+    ///     rows.order_by_asc(&name);
+    ///     rows.into_vec(name)
+    /// });
+    /// assert_eq!(names, ["alice", "Carol"]);
+    /// ```
+    pub fn collate(&self, collation: &str) -> Column<'column, S, String> {
+        Column::new(Collate(self.inner.clone(), collation.to_owned()))
+    }
+
+    /// Check if the string comes before the other string.
+    ///
+    /// Strings are compared byte-wise using the default `BINARY` collation of sqlite,
+    /// unless a different collation is specified with [Column::collate].
+    /// This means that all uppercase ascii letters come before all lowercase ascii letters.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, Database, IntoColumn, LocalClient};
+    /// # #[schema]
+    /// # enum Schema {}
+    /// # use v0::*;
+    /// # fn main() {
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let txn = client.transaction(&database);
+    /// let lt = |a: &str, b: &str| txn.query_one(a.into_column().lt(b));
+    /// assert!(lt("Alice", "Bob"));
+    /// assert!(lt("Bob", "alice"));
+    /// assert!(lt("", "a"));
+    /// assert!(!lt("Bob", "Bob"));
+    /// # }
+    /// ```
+    pub fn lt(&self, rhs: impl IntoColumn<'column, S, Typ = String>) -> Column<'column, S, bool> {
+        Column::new(Lt(self.inner.clone(), rhs.into_column().inner))
+    }
+}
+
+impl<'column, S> Column<'column, S, Vec<u8>> {
+    /// Check if the blob comes before the other blob.
+    ///
+    /// Blobs are compared byte-wise, a blob that is a prefix of another blob comes first.
+    pub fn lt(&self, rhs: impl IntoColumn<'column, S, Typ = Vec<u8>>) -> Column<'column, S, bool> {
+        Column::new(Lt(self.inner.clone(), rhs.into_column().inner))
+    }
 }
 
+/// Use the value of `then` if `cond` is true, otherwise use the value of `otherwise`.
+///
+/// This is the `CASE WHEN cond THEN then ELSE otherwise END` expression of sqlite.
+/// Multiple branches can be expressed by nesting [if_then] in `otherwise`.
+pub fn if_then<'column, S, T: MyTyp>(
+    cond: impl IntoColumn<'column, S, Typ = bool>,
+    then: impl IntoColumn<'column, S, Typ = T>,
+    otherwise: impl IntoColumn<'column, S, Typ = T>,
+) -> Column<'column, S, T> {
+    Column::new(Case(
+        cond.into_column().inner,
+        then.into_column().inner,
+        otherwise.into_column().inner,
+    ))
+}
+
+/// Call a custom sql function that was registered with [crate::migration::Config::scalar_function].
+///
+/// The arguments are given as a tuple of up to four columns.
+/// The type `T` needs to match the values returned by the function,
+/// use [Option] if the function can return `NULL`.
+/// ```
+/// # use rust_query::{call_fn, migration::{schema, Config}, Database, LocalClient};
+/// # #[schema]
+/// # enum Schema {
+/// #     Player { score: f64 },
+/// # }
+/// # use v0::*;
+/// # let mut client = LocalClient::try_new().unwrap();
+/// let config = Config::open_in_memory().scalar_function("my_score", 2, |ctx| {
+///     Ok(ctx.get::<f64>(0)? * 2. + ctx.get::<f64>(1)?)
+/// });
+/// # let database: Database<Schema> = client.migrator(config).unwrap().finish().unwrap();
+/// # let txn = client.transaction(&database);
+/// let score = txn.query_one(call_fn::<f64, _>("my_score", (1.5, 0.25)));
+/// assert_eq!(score, 3.25);
+/// ```
+pub fn call_fn<'column, T: MyTyp, S>(
+    name: &'static str,
+    args: impl FnArgs<'column, S>,
+) -> Column<'column, S, T> {
+    Column::new(CallFn {
+        name,
+        args: args.into_exprs(),
+        _p: PhantomData,
+    })
+}
+
+/// Use a fragment of raw sql as an expression.
+///
+/// This is an escape hatch for sqlite features that are not wrapped by this crate.
+/// Every `?` in `template` is replaced by the next argument, except inside quoted strings.
+/// The arguments are given as a tuple of up to four columns, just like for [call_fn].
+/// Building the query panics if there are more placeholders than arguments.
+///
+/// **It is the responsibility of the caller that the type `T` matches the values returned by the sql.**
+/// Use [Option] if the sql can return `NULL`.
+/// A mismatched type will result in a panic when the value is read.
+/// ```
+/// # use rust_query::{raw_sql, migration::{schema, Config}, Database, LocalClient};
+/// # #[schema]
+/// # enum Schema {
+/// #     Event { payload: String },
+/// # }
+/// # use v0::*;
+/// # let mut client = LocalClient::try_new().unwrap();
+/// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+/// # let mut txn = client.transaction_mut(&database);
+/// let event = txn.insert(Event { payload: r#"{"user": "alice"}"# });
+/// let user = txn.query_one(raw_sql::<Option<String>, _>(
+///     "json_extract(?, '$.user')",
+///     (event.payload(),),
+/// ));
+/// assert_eq!(user.as_deref(), Some("alice"));
+/// ```
+pub fn raw_sql<'column, T: MyTyp, S>(
+    template: &str,
+    args: impl FnArgs<'column, S>,
+) -> Column<'column, S, T> {
+    Column::new(RawSql {
+        template: template.to_owned(),
+        args: args.into_exprs(),
+        _p: PhantomData,
+    })
+}
+
+/// Join strings with a separator, skipping the values that are [None].
+///
+/// This works like the sqlite `concat_ws` function, which is only available in newer versions of sqlite.
+/// The result is an empty string if all values are [None].
+/// Columns that are never [None] can be converted with `Some(column).into_column()`.
+/// ```
+/// # use rust_query::{concat_ws, migration::{schema, Config}, Database, IntoColumn, LocalClient};
+/// # #[schema]
+/// # enum Schema {
+/// #     Person { first: String, middle: Option<String>, last: String },
+/// # }
+/// # use v0::*;
+/// # let mut client = LocalClient::try_new().unwrap();
+/// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+/// # let mut txn = client.transaction_mut(&database);
+/// let ada = txn.insert(Person { first: "Ada", middle: None::<String>, last: "Lovelace" });
+/// let name = txn.query_one(concat_ws(" ", [
+///     Some(ada.first()).into_column(),
+///     ada.middle(),
+///     Some(ada.last()).into_column(),
+/// ]));
+/// assert_eq!(name, "Ada Lovelace");
+/// ```
+pub fn concat_ws<'column, S>(
+    separator: &str,
+    values: impl IntoIterator<Item = impl IntoColumn<'column, S, Typ = Option<String>>>,
+) -> Column<'column, S, String> {
+    let values = values.into_iter().map(|x| x.into_column().inner);
+    Column::new(ConcatWs(separator.to_owned(), values.collect()))
+}
+
+/// Arguments of a custom sql function, see [crate::call_fn] and [crate::raw_sql].
+///
+/// This trait is implemented for tuples of up to four [IntoColumn] values.
+pub trait FnArgs<'column, S> {
+    #[doc(hidden)]
+    fn into_exprs(self) -> Vec<DynTypedExpr>;
+}
+
+macro_rules! fn_args {
+    ($($name:ident),*) => {
+        impl<'column, S, $($name: IntoColumn<'column, S>),*> FnArgs<'column, S> for ($($name,)*) {
+            #[allow(non_snake_case)]
+            fn into_exprs(self) -> Vec<DynTypedExpr> {
+                let ($($name,)*) = self;
+                vec![$($name.into_column().inner.erase()),*]
+            }
+        }
+    };
+}
+
+fn_args!();
+fn_args!(A);
+fn_args!(A, B);
+fn_args!(A, B, C);
+fn_args!(A, B, C, D);
+
 impl<T: Typed<Typ = X>, X: MyTyp<Sql: Nullable>> Typed for Option<T> {
     type Typ = Option<T::Typ>;
 
@@ -302,6 +871,54 @@ impl<'column, S> IntoColumn<'column, S> for bool {
     }
 }
 
+impl Typed for &[u8] {
+    type Typ = Vec<u8>;
+    fn build_expr(&self, _: ValueBuilder) -> SimpleExpr {
+        SimpleExpr::from(self.to_vec())
+    }
+}
+
+impl Private for &[u8] {}
+impl<'column, S> IntoColumn<'column, S> for &[u8] {
+    type Typ = Vec<u8>;
+    fn into_column(self) -> Column<'column, S, Self::Typ> {
+        Column::new(self.to_owned())
+    }
+}
+
+impl Typed for Vec<u8> {
+    type Typ = Vec<u8>;
+    fn build_expr(&self, _: ValueBuilder) -> SimpleExpr {
+        SimpleExpr::from(self.clone())
+    }
+}
+
+impl Private for Vec<u8> {}
+impl<'column, S> IntoColumn<'column, S> for Vec<u8> {
+    type Typ = Vec<u8>;
+    fn into_column(self) -> Column<'column, S, Self::Typ> {
+        Column::new(self)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl Typed for uuid::Uuid {
+    type Typ = uuid::Uuid;
+    fn build_expr(&self, _: ValueBuilder) -> SimpleExpr {
+        SimpleExpr::from(self.as_bytes().to_vec())
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl Private for uuid::Uuid {}
+#[cfg(feature = "uuid")]
+impl<'column, S> IntoColumn<'column, S> for uuid::Uuid {
+    type Typ = uuid::Uuid;
+    fn into_column(self) -> Column<'column, S, Self::Typ> {
+        Column::new(self)
+    }
+}
+
 impl Typed for i64 {
     type Typ = i64;
     fn build_expr(&self, _: ValueBuilder) -> SimpleExpr {
@@ -332,6 +949,25 @@ impl<'column, S> IntoColumn<'column, S> for f64 {
     }
 }
 
+/// A [Duration] is stored as an [i64] number of milliseconds.
+///
+/// Any precision below one millisecond is truncated.
+impl Typed for Duration {
+    type Typ = i64;
+    fn build_expr(&self, _: ValueBuilder) -> SimpleExpr {
+        let millis = i64::try_from(self.as_millis()).expect("duration is too large");
+        SimpleExpr::from(millis)
+    }
+}
+
+impl Private for Duration {}
+impl<'column, S> IntoColumn<'column, S> for Duration {
+    type Typ = i64;
+    fn into_column(self) -> Column<'column, S, Self::Typ> {
+        Column::new(self)
+    }
+}
+
 impl<T> Typed for &T
 where
     T: Typed,
@@ -378,6 +1014,43 @@ impl<'column, S> IntoColumn<'column, S> for UnixEpoch {
     }
 }
 
+/// Use this a value in a query to get the current datetime as a string.
+///
+/// This uses the sqlite `datetime('now')` function, so the format is `YYYY-MM-DD HH:MM:SS` in UTC.
+/// ```
+/// # use rust_query::{migration::{schema, Config}, Database, LocalClient, NowString};
+/// # #[schema]
+/// # enum Schema {
+/// #     Invoice { date: String },
+/// # }
+/// # use v0::*;
+/// # let mut client = LocalClient::try_new().unwrap();
+/// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+/// # let mut txn = client.transaction_mut(&database);
+/// let invoice = txn.insert(Invoice { date: NowString });
+/// let date = txn.query_one(invoice.date());
+/// let (day, time) = date.split_once(' ').unwrap();
+/// assert_eq!(day.split('-').map(|x| x.parse::<u32>().unwrap()).count(), 3);
+/// assert_eq!(time.split(':').map(|x| x.parse::<u32>().unwrap()).count(), 3);
+/// ```
+#[derive(Clone, Copy)]
+pub struct NowString;
+
+impl Typed for NowString {
+    type Typ = String;
+    fn build_expr(&self, _: ValueBuilder) -> SimpleExpr {
+        Expr::col(RawAlias("datetime('now')".to_owned())).into()
+    }
+}
+
+impl Private for NowString {}
+impl<'column, S> IntoColumn<'column, S> for NowString {
+    type Typ = String;
+    fn into_column(self) -> Column<'column, S, Self::Typ> {
+        Column::new(self)
+    }
+}
+
 pub trait MyTyp: 'static {
     #[doc(hidden)]
     const NULLABLE: bool = false;
@@ -404,12 +1077,17 @@ impl<T: Table> MyTyp for T {
 
 impl<T> SecretFromSql for TableRow<'_, T> {
     fn from_sql(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        let idx = match value {
+            // sqlite can return the id as a real from `INSERT .. RETURNING` for tables with real columns
+            rusqlite::types::ValueRef::Real(x) if x.fract() == 0. => x as i64,
+            _ => value.as_i64()?,
+        };
         Ok(TableRow {
             _p: PhantomData,
             _local: PhantomData,
             inner: TableRowInner {
                 _p: PhantomData,
-                idx: value.as_i64()?,
+                idx,
             },
         })
     }
@@ -435,7 +1113,11 @@ impl MyTyp for f64 {
 
 impl SecretFromSql for f64 {
     fn from_sql(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
-        value.as_f64()
+        match value {
+            // sqlite can return integers for real expressions, e.g. `IFNULL(SUM(x), 0.0)`
+            rusqlite::types::ValueRef::Integer(x) => Ok(x as f64),
+            _ => value.as_f64(),
+        }
     }
 }
 
@@ -463,6 +1145,33 @@ impl SecretFromSql for String {
     }
 }
 
+impl MyTyp for Vec<u8> {
+    const TYP: hash::ColumnType = hash::ColumnType::Blob;
+    type Out<'t> = Self;
+    type Sql = Vec<u8>;
+}
+
+impl SecretFromSql for Vec<u8> {
+    fn from_sql(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        Ok(value.as_blob()?.to_owned())
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl MyTyp for uuid::Uuid {
+    const TYP: hash::ColumnType = hash::ColumnType::Blob;
+    type Out<'t> = Self;
+    type Sql = Vec<u8>;
+}
+
+#[cfg(feature = "uuid")]
+impl SecretFromSql for uuid::Uuid {
+    fn from_sql(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        uuid::Uuid::from_slice(value.as_blob()?)
+            .map_err(|err| rusqlite::types::FromSqlError::Other(Box::new(err)))
+    }
+}
+
 impl<T: MyTyp> MyTyp for Option<T> {
     const TYP: hash::ColumnType = T::TYP;
     const NULLABLE: bool = true;
@@ -616,3 +1325,15 @@ fn escape_glob(s: impl AsRef<str>) -> String {
     }
     escaped
 }
+
+/// Escape the special characters of a `LIKE` pattern with `\`.
+fn escape_like(s: impl AsRef<str>) -> String {
+    let mut escaped = String::new();
+    for c in s.as_ref().chars() {
+        if let '%' | '_' | '\\' = c {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -0,0 +1,135 @@
+use std::marker::PhantomData;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sea_query::{Expr, SimpleExpr};
+
+use crate::{
+    alias::RawAlias,
+    dummy_impl::{ColumnImpl, Dummy},
+    hash, IntoDummy,
+};
+
+use super::{
+    operations::Lt,
+    trivial::{FromColumn, FromDummy},
+    Column, EqTyp, IntoColumn, MyTyp, OrdTyp, Private, SecretFromSql, Typed, ValueBuilder,
+};
+
+/// Datetimes are stored as text with a fixed number of digits.
+/// This makes sure that the text order is the same as the datetime order.
+/// The format is also understood by the sqlite date and time functions.
+const FORMAT: &str = "%Y-%m-%d %H:%M:%S%.9f";
+
+/// The formatted value of a datetime column.
+struct DateTimeText<T> {
+    text: String,
+    _p: PhantomData<T>,
+}
+
+impl<T: MyTyp> Typed for DateTimeText<T> {
+    type Typ = T;
+    fn build_expr(&self, _: ValueBuilder) -> SimpleExpr {
+        SimpleExpr::from(&self.text)
+    }
+}
+
+fn parse(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value.as_str()?, "%Y-%m-%d %H:%M:%S%.f")
+        .map_err(|err| rusqlite::types::FromSqlError::Other(Box::new(err)))
+}
+
+macro_rules! datetime {
+    ($typ:ty, $naive:expr, $from_naive:expr) => {
+        impl MyTyp for $typ {
+            const TYP: hash::ColumnType = hash::ColumnType::String;
+            type Out<'t> = Self;
+            type Sql = String;
+        }
+
+        impl SecretFromSql for $typ {
+            fn from_sql(
+                value: rusqlite::types::ValueRef<'_>,
+            ) -> rusqlite::types::FromSqlResult<Self> {
+                parse(value).map($from_naive)
+            }
+        }
+
+        impl EqTyp for $typ {}
+        impl OrdTyp for $typ {}
+
+        impl Private for $typ {}
+        impl<'column, S> IntoColumn<'column, S> for $typ {
+            type Typ = $typ;
+            fn into_column(self) -> Column<'column, S, Self::Typ> {
+                let naive: fn(&$typ) -> NaiveDateTime = $naive;
+                Column::new(DateTimeText {
+                    text: naive(&self).format(FORMAT).to_string(),
+                    _p: PhantomData,
+                })
+            }
+        }
+
+        impl FromDummy for $typ {
+            type Impl = ColumnImpl<Self>;
+        }
+        impl<'transaction, S> FromColumn<'transaction, S, $typ> for $typ {
+            fn from_column<'columns>(
+                col: Column<'columns, S, $typ>,
+            ) -> Dummy<'columns, 'transaction, S, Self::Impl> {
+                col.into_dummy()
+            }
+        }
+
+        impl<'column, S> Column<'column, S, $typ> {
+            /// Check if this datetime is before the other datetime.
+            pub fn lt(
+                &self,
+                rhs: impl IntoColumn<'column, S, Typ = $typ>,
+            ) -> Column<'column, S, bool> {
+                Column::new(Lt(self.inner.clone(), rhs.into_column().inner))
+            }
+        }
+    };
+}
+
+datetime!(NaiveDateTime, |x| *x, |x| x);
+datetime!(DateTime<Utc>, |x| x.naive_utc(), |x| x.and_utc());
+
+/// Use this a value in a query to get the current datetime as a [DateTime<Utc>].
+///
+/// Datetime columns are stored as text in the format `YYYY-MM-DD HH:MM:SS.SSSSSSSSS`.
+/// Unlike [crate::UnixEpoch] and [crate::NowString], this includes the milliseconds.
+/// ```
+/// # use rust_query::{migration::{schema, Config}, Database, LocalClient, UtcNow};
+/// # #[schema]
+/// # enum Schema {
+/// #     Invoice { created: chrono::DateTime<chrono::Utc> },
+/// # }
+/// # use v0::*;
+/// # let mut client = LocalClient::try_new().unwrap();
+/// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+/// # let mut txn = client.transaction_mut(&database);
+/// let before = chrono::Utc::now() - chrono::Duration::seconds(1);
+/// let invoice = txn.insert(Invoice { created: UtcNow });
+/// assert!(txn.query_one(invoice.created()) > before);
+/// assert!(txn.query_one(invoice.created().lt(before)) == false);
+/// ```
+#[derive(Clone, Copy)]
+pub struct UtcNow;
+
+impl Typed for UtcNow {
+    type Typ = DateTime<Utc>;
+    fn build_expr(&self, _: ValueBuilder) -> SimpleExpr {
+        // sqlite only has milliseconds, the other digits are added to match the format
+        let sql = "strftime('%Y-%m-%d %H:%M:%f', 'now') || '000000'";
+        Expr::col(RawAlias(sql.to_owned())).into()
+    }
+}
+
+impl Private for UtcNow {}
+impl<'column, S> IntoColumn<'column, S> for UtcNow {
+    type Typ = DateTime<Utc>;
+    fn into_column(self) -> Column<'column, S, Self::Typ> {
+        Column::new(self)
+    }
+}
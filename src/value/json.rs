@@ -0,0 +1,124 @@
+use std::marker::PhantomData;
+
+use sea_query::SimpleExpr;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    dummy_impl::{ColumnImpl, Dummy},
+    hash, IntoDummy,
+};
+
+use super::{
+    call_fn,
+    trivial::{FromColumn, FromDummy},
+    Column, IntoColumn, MyTyp, Private, SecretFromSql, Typed, ValueBuilder,
+};
+
+/// Column type for values that are stored as JSON text.
+///
+/// The value is serialized with [serde_json] when it is written and deserialized when it is read.
+/// The type `T` needs to implement [Clone] to use a [Json] value in a query, a reference `&Json<T>` can be used otherwise.
+/// Parts of the value can be retrieved in a query with [Column::get].
+/// Just like default values, the column type is used inside the generated module, so it is best to use absolute paths.
+/// ```
+/// use rust_query::{migration::{schema, Config}, Database, Json, LocalClient};
+///
+/// #[derive(serde::Serialize, serde::Deserialize, Clone)]
+/// pub struct Settings {
+///     pub theme: String,
+///     pub volume: i64,
+/// }
+///
+/// #[schema]
+/// enum Schema {
+///     User {
+///         name: String,
+///         settings: rust_query::Json<crate::Settings>,
+///     },
+/// }
+/// use v0::*;
+///
+/// # fn main() {
+/// # let mut client = LocalClient::try_new().unwrap();
+/// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+/// # let mut txn = client.transaction_mut(&database);
+/// let settings = Settings { theme: "dark".to_owned(), volume: 7 };
+/// let alice = txn.insert(User { name: "Alice", settings: Json(settings) });
+///
+/// let volume = txn.query_one(alice.settings().get::<i64>("$.volume"));
+/// assert_eq!(volume, Some(7));
+///
+/// let Json(settings) = txn.query_one(alice.settings());
+/// assert_eq!(settings.theme, "dark");
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Json<T>(pub T);
+
+impl<T: Serialize + DeserializeOwned + 'static> MyTyp for Json<T> {
+    const TYP: hash::ColumnType = hash::ColumnType::String;
+    type Out<'t> = Self;
+    type Sql = String;
+}
+
+impl<T: DeserializeOwned> SecretFromSql for Json<T> {
+    fn from_sql(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        serde_json::from_str(value.as_str()?)
+            .map(Json)
+            .map_err(|err| rusqlite::types::FromSqlError::Other(Box::new(err)))
+    }
+}
+
+/// The serialized value of a [Json] column.
+struct JsonText<T> {
+    text: String,
+    _p: PhantomData<T>,
+}
+
+impl<T: Serialize + DeserializeOwned + 'static> Typed for JsonText<T> {
+    type Typ = Json<T>;
+    fn build_expr(&self, _: ValueBuilder) -> SimpleExpr {
+        SimpleExpr::from(&self.text)
+    }
+}
+
+impl<T> Private for Json<T> {}
+impl<'column, S, T: Serialize + DeserializeOwned + Clone + 'static> IntoColumn<'column, S>
+    for Json<T>
+{
+    type Typ = Json<T>;
+    fn into_column(self) -> Column<'column, S, Self::Typ> {
+        Column::new(JsonText {
+            text: serde_json::to_string(&self.0).expect("value can be serialized as json"),
+            _p: PhantomData,
+        })
+    }
+}
+
+impl<T: DeserializeOwned> FromDummy for Json<T> {
+    type Impl = ColumnImpl<Self>;
+}
+impl<'transaction, S, T: Serialize + DeserializeOwned + 'static>
+    FromColumn<'transaction, S, Json<T>> for Json<T>
+{
+    fn from_column<'columns>(
+        col: Column<'columns, S, Json<T>>,
+    ) -> Dummy<'columns, 'transaction, S, Self::Impl> {
+        col.into_dummy()
+    }
+}
+
+impl<'column, S, T: Serialize + DeserializeOwned + 'static> Column<'column, S, Json<T>> {
+    /// Retrieve the value at `path` in the JSON, using the sqlite `json_extract` function.
+    ///
+    /// The path has the form `$.field[0]`, see the sqlite documentation for details.
+    /// The result is [None] if there is nothing at `path` or if the value is `null`.
+    ///
+    /// JSON numbers, strings and booleans are converted to the corresponding sqlite values,
+    /// so `U` can be [i64], [f64], [String] or [bool].
+    /// JSON objects and arrays are returned as JSON text, so `U` can be [Json] for those.
+    /// It is the responsibility of the caller that the type `U` matches the value at `path`.
+    pub fn get<U: MyTyp>(&self, path: &str) -> Column<'column, S, Option<U>> {
+        call_fn("json_extract", (self, path))
+    }
+}
@@ -1,6 +1,11 @@
-use sea_query::{extension::sqlite::SqliteExpr, Alias, Expr, Keyword, LikeExpr, SimpleExpr};
+use sea_query::{
+    extension::sqlite::SqliteExpr, Alias, BinOper, Expr, Func, Keyword, LikeExpr, Order,
+    SelectStatement, SimpleExpr,
+};
 
-use super::{NumTyp, Typed, ValueBuilder};
+use std::{marker::PhantomData, rc::Rc};
+
+use super::{DynTypedExpr, NumTyp, Typed, ValueBuilder};
 
 #[derive(Clone, Copy)]
 pub struct Add<A, B>(pub(crate) A, pub(crate) B);
@@ -52,6 +57,20 @@ impl<A: Typed, B: Typed> Typed for Eq<A, B> {
     }
 }
 
+#[derive(Clone)]
+pub struct In<A, B>(pub(crate) A, pub(crate) Vec<B>);
+
+impl<A: Typed, B: Typed> Typed for In<A, B> {
+    type Typ = bool;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        if self.1.is_empty() {
+            return SimpleExpr::Constant(false.into());
+        }
+        let values = self.1.iter().map(|x| x.build_expr(b));
+        Expr::expr(self.0.build_expr(b)).is_in(values)
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct UnwrapOr<A, B>(pub(crate) A, pub(crate) B);
 
@@ -62,6 +81,16 @@ impl<A: Typed, B: Typed> Typed for UnwrapOr<A, B> {
     }
 }
 
+pub struct InQuery<A>(pub(crate) A, pub(crate) SelectStatement);
+
+impl<A: Typed> Typed for InQuery<A> {
+    type Typ = bool;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        // the sub-query does not refer to the outer query, because the aliases could overlap
+        Expr::expr(self.0.build_expr(b)).in_subquery(self.1.clone())
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct Not<T>(pub(crate) T);
 
@@ -95,6 +124,19 @@ impl<A: Typed<Typ = bool>, B: Typed<Typ = Option<T>>, T> Typed for NullIf<A, B>
     }
 }
 
+#[derive(Clone, Copy)]
+/// Return `B` if `A` is `true` else `C`
+pub struct Case<A, B, C>(pub(crate) A, pub(crate) B, pub(crate) C);
+
+impl<A: Typed<Typ = bool>, B: Typed, C: Typed<Typ = B::Typ>> Typed for Case<A, B, C> {
+    type Typ = B::Typ;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        Expr::case(self.0.build_expr(b), self.1.build_expr(b))
+            .finally(self.2.build_expr(b))
+            .into()
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct Assume<A>(pub(crate) A);
 
@@ -115,13 +157,137 @@ impl<A: Typed> Typed for AsFloat<A> {
     }
 }
 
+#[derive(Clone, Copy)]
+pub struct CastInt<A>(pub(crate) A);
+
+impl<A: Typed> Typed for CastInt<A> {
+    type Typ = i64;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        self.0.build_expr(b).cast_as(Alias::new("integer"))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Round<A>(pub(crate) A, pub(crate) i64);
+
+impl<A: Typed> Typed for Round<A> {
+    type Typ = f64;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        Func::round_with_precision(self.0.build_expr(b), self.1).into()
+    }
+}
+
+/// Floats with a larger magnitude than this are always integers.
+const INTEGRAL_FLOAT: f64 = 4503599627370496.;
+
+#[derive(Clone, Copy)]
+pub struct Floor<A>(pub(crate) A);
+
+impl<A: Typed> Typed for Floor<A> {
+    type Typ = f64;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        let x = self.0.build_expr(b);
+        let int = x.clone().cast_as(Alias::new("integer"));
+        let res = int.clone().sub(Expr::expr(x.clone()).lt(int));
+        integral_or(x, res)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Ceil<A>(pub(crate) A);
+
+impl<A: Typed> Typed for Ceil<A> {
+    type Typ = f64;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        let x = self.0.build_expr(b);
+        let int = x.clone().cast_as(Alias::new("integer"));
+        let res = int.clone().add(Expr::expr(x.clone()).gt(int));
+        integral_or(x, res)
+    }
+}
+
+/// Use `x` if it is too large to have a fractional part, otherwise use `res` as a float.
+///
+/// This prevents `CAST(x AS INTEGER)` from saturating.
+fn integral_or(x: SimpleExpr, res: SimpleExpr) -> SimpleExpr {
+    let abs = Func::abs(x.clone());
+    Expr::case(Expr::expr(abs).gte(INTEGRAL_FLOAT), x)
+        .finally(res.cast_as(Alias::new("real")))
+        .into()
+}
+
+#[derive(Clone, Copy)]
+pub struct Sqrt<A>(pub(crate) A);
+
+impl<A: Typed> Typed for Sqrt<A> {
+    type Typ = Option<f64>;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        Func::cust(Alias::new("sqrt"))
+            .arg(self.0.build_expr(b))
+            .into()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Pow<A, B>(pub(crate) A, pub(crate) B);
+
+impl<A: Typed, B: Typed> Typed for Pow<A, B> {
+    type Typ = Option<f64>;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        Func::cust(Alias::new("pow"))
+            .args([self.0.build_expr(b), self.1.build_expr(b)])
+            .into()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct SqliteTypeof<A>(pub(crate) A);
+
+impl<A: Typed> Typed for SqliteTypeof<A> {
+    type Typ = String;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        Func::cust(Alias::new("typeof"))
+            .arg(self.0.build_expr(b))
+            .into()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ParseInt<A>(pub(crate) A);
+
+impl<A: Typed> Typed for ParseInt<A> {
+    type Typ = Option<i64>;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        let text = self.0.build_expr(b);
+        let int = text.clone().cast_as(Alias::new("integer"));
+        let round_trip = int.clone().cast_as(Alias::new("text"));
+        Expr::case(round_trip.eq(text), int).into()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ParseFloat<A>(pub(crate) A);
+
+impl<A: Typed> Typed for ParseFloat<A> {
+    type Typ = Option<f64>;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        let text = self.0.build_expr(b);
+        // `json_type` raises an error on invalid json, so it needs to be guarded
+        let valid = Func::cust(Alias::new("json_valid")).arg(text.clone());
+        let typ = Func::cust(Alias::new("json_type")).arg(text.clone());
+        let float = text.cast_as(Alias::new("real"));
+        let is_num = Expr::expr(typ).is_in(["integer", "real"]);
+        Expr::case(SimpleExpr::from(valid), Expr::case(is_num, float)).into()
+    }
+}
+
 #[derive(Clone)]
-pub struct Like<A>(pub(crate) A, pub(crate) String);
+pub struct Like<A>(pub(crate) A, pub(crate) String, pub(crate) char);
 
 impl<A: Typed> Typed for Like<A> {
     type Typ = bool;
     fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
-        Expr::expr(self.0.build_expr(b)).like(LikeExpr::new(&self.1).escape('\\'))
+        Expr::expr(self.0.build_expr(b)).like(LikeExpr::new(&self.1).escape(self.2))
     }
 }
 
@@ -135,6 +301,50 @@ impl<A: Typed, B: Typed> Typed for Glob<A, B> {
     }
 }
 
+#[derive(Clone)]
+pub struct Collate<A>(pub(crate) A, pub(crate) String);
+
+impl<A: Typed> Typed for Collate<A> {
+    type Typ = A::Typ;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        let collation = self.1.replace('"', "\"\"");
+        Expr::cust_with_expr(format!("(?) COLLATE \"{collation}\""), self.0.build_expr(b))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Concat<A, B>(pub(crate) A, pub(crate) B);
+
+impl<A: Typed, B: Typed> Typed for Concat<A, B> {
+    type Typ = A::Typ;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        self.0
+            .build_expr(b)
+            .binary(BinOper::Custom("||"), self.1.build_expr(b))
+    }
+}
+
+#[derive(Clone)]
+pub struct ConcatWs<A>(pub(crate) String, pub(crate) Vec<A>);
+
+impl<A: Typed> Typed for ConcatWs<A> {
+    type Typ = String;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        // `concat_ws` is only available since sqlite 3.44.0, so it is written out instead.
+        // Every value is prefixed with the separator, and the first separator is removed.
+        let sep = || SimpleExpr::from(&self.0);
+        let parts = self.1.iter().map(|x| {
+            let part = sep().binary(BinOper::Custom("||"), x.build_expr(b));
+            Func::coalesce([part, SimpleExpr::from("")]).into()
+        });
+        let all = parts
+            .reduce(|a: SimpleExpr, x| a.binary(BinOper::Custom("||"), x))
+            .unwrap_or(SimpleExpr::from(""));
+        let start = SimpleExpr::from(self.0.chars().count() as i64 + 1);
+        Func::cust(Alias::new("substr")).args([all, start]).into()
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct Const<A>(pub(crate) A);
 
@@ -144,3 +354,73 @@ impl<A: NumTyp> Typed for Const<A> {
         SimpleExpr::Constant(self.0.into_sea_value())
     }
 }
+
+pub struct CallFn<T> {
+    pub(crate) name: &'static str,
+    pub(crate) args: Vec<DynTypedExpr>,
+    pub(crate) _p: PhantomData<T>,
+}
+
+impl<T> Typed for CallFn<T> {
+    type Typ = T;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        let args = self.args.iter().map(|arg| (arg.0)(b));
+        Func::cust(Alias::new(self.name)).args(args).into()
+    }
+}
+
+pub struct RawSql<T> {
+    pub(crate) template: String,
+    pub(crate) args: Vec<DynTypedExpr>,
+    pub(crate) _p: PhantomData<T>,
+}
+
+impl<T> Typed for RawSql<T> {
+    type Typ = T;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        let args = self.args.iter().map(|arg| (arg.0)(b));
+        Expr::cust_with_exprs(format!("({})", self.template), args)
+    }
+}
+
+pub struct Over<T> {
+    pub(crate) name: &'static str,
+    pub(crate) args: Vec<DynTypedExpr>,
+    pub(crate) partition: Vec<Rc<DynTypedExpr>>,
+    pub(crate) order: Vec<(Rc<DynTypedExpr>, Order)>,
+    pub(crate) frame: Option<&'static str>,
+    pub(crate) _p: PhantomData<T>,
+}
+
+impl<T> Typed for Over<T> {
+    type Typ = T;
+    fn build_expr(&self, b: ValueBuilder) -> SimpleExpr {
+        let mut exprs: Vec<_> = self.args.iter().map(|arg| (arg.0)(b)).collect();
+        let args = vec!["(?)"; self.args.len()].join(", ");
+
+        let mut window = vec![];
+        if !self.partition.is_empty() {
+            let partition = vec!["(?)"; self.partition.len()].join(", ");
+            window.push(format!("PARTITION BY {partition}"));
+            exprs.extend(self.partition.iter().map(|expr| (expr.0)(b)));
+        }
+        if !self.order.is_empty() {
+            let order: Vec<_> = self
+                .order
+                .iter()
+                .map(|(expr, order)| {
+                    exprs.push((expr.0)(b));
+                    match order {
+                        Order::Desc => "(?) DESC",
+                        _ => "(?) ASC",
+                    }
+                })
+                .collect();
+            window.push(format!("ORDER BY {}", order.join(", ")));
+        }
+        window.extend(self.frame.map(str::to_owned));
+
+        let template = format!("{}({args}) OVER ({})", self.name, window.join(" "));
+        Expr::cust_with_exprs(template, exprs)
+    }
+}
@@ -30,6 +30,30 @@ pub fn optional<'outer, S, R>(
 ///
 /// Joining more optional columns can be done with the [Optional::and] method.
 /// Finally it is possible to return either columns or dummies using [Optional::then] and [Optional::then_dummy].
+/// A fallback value instead of [None] can be given with [Optional::then_or].
+///
+/// The columns that are joined with [Optional::and] can be used for multiple results.
+/// [Optional] does not implement [Clone], because the joined columns of a clone would
+/// not be checked by the original [Optional].
+/// ```
+/// # use rust_query::{migration::{schema, Config}, optional, Database, LocalClient, TableRow};
+/// # #[schema]
+/// # enum Schema {
+/// #     Customer { name: String, referrer: Option<Customer> },
+/// # }
+/// # use v0::*;
+/// # let mut client = LocalClient::try_new().unwrap();
+/// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+/// # let mut txn = client.transaction_mut(&database);
+/// let alice = txn.insert(Customer { name: "Alice", referrer: None::<TableRow<Customer>> });
+/// let bob = txn.insert(Customer { name: "Bob", referrer: Some(alice) });
+/// let (name, referrer) = txn.query_one(optional(|row| {
+///     let referrer = row.and(bob.referrer());
+///     (row.then(referrer.name()), row.then(referrer))
+/// }));
+/// assert_eq!(name.as_deref(), Some("Alice"));
+/// assert_eq!(referrer, Some(alice));
+/// ```
 pub struct Optional<'outer, 'inner, S> {
     nulls: Vec<DynTyped<bool>>,
     _p: PhantomData<&'inner &'outer ()>,
@@ -61,6 +85,17 @@ impl<'outer, 'inner, S> Optional<'outer, 'inner, S> {
             .rfold(res, |accum, e| Column::new(NullIf(e.clone(), accum.inner)))
     }
 
+    /// Return the column if the current row exists and the `fallback` column otherwise.
+    ///
+    /// This is the same as [Optional::then] followed by [Column::unwrap_or].
+    pub fn then_or<T: MyTyp<Sql: Nullable> + 'outer>(
+        &self,
+        col: impl IntoColumn<'inner, S, Typ = T>,
+        fallback: impl IntoColumn<'outer, S, Typ = T>,
+    ) -> Column<'outer, S, T> {
+        self.then(col).unwrap_or(fallback)
+    }
+
     /// Return a [bool] column indicating whether the current row exists.
     pub fn is_some(&self) -> Column<'outer, S, bool> {
         let any_null = self
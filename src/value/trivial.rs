@@ -1,7 +1,7 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, time::Duration};
 
 use crate::{
-    dummy_impl::{ColumnImpl, Dummy, DummyImpl},
+    dummy_impl::{ColumnImpl, Dummy, DummyImpl, MapImpl},
     optional, IntoDummy, Table, TableRow,
 };
 
@@ -67,6 +67,47 @@ from_column! {String}
 from_column! {i64}
 from_column! {f64}
 from_column! {bool}
+from_column! {Vec<u8>}
+#[cfg(feature = "uuid")]
+from_column! {uuid::Uuid}
+
+impl FromDummy for Duration {
+    type Impl = MapImpl<ColumnImpl<i64>, fn(i64) -> Duration>;
+}
+/// Read a number of milliseconds as a [Duration].
+///
+/// This panics if the number of milliseconds is negative.
+impl<'transaction, S> FromColumn<'transaction, S, i64> for Duration {
+    fn from_column<'columns>(
+        col: Column<'columns, S, i64>,
+    ) -> Dummy<'columns, 'transaction, S, Self::Impl> {
+        col.into_dummy().map_dummy(|millis| {
+            let millis = u64::try_from(millis).expect("duration can not be negative");
+            Duration::from_millis(millis)
+        })
+    }
+}
+
+impl FromDummy for f32 {
+    type Impl = MapImpl<ColumnImpl<f64>, fn(f64) -> f32>;
+}
+/// Read an [f64] column as an [f32].
+///
+/// Precision is lost, but this panics if the value is too large for an [f32].
+impl<'transaction, S> FromColumn<'transaction, S, f64> for f32 {
+    fn from_column<'columns>(
+        col: Column<'columns, S, f64>,
+    ) -> Dummy<'columns, 'transaction, S, Self::Impl> {
+        col.into_dummy().map_dummy(|x| {
+            let res = x as f32;
+            assert!(
+                !x.is_finite() || res.is_finite(),
+                "value is too large for an f32"
+            );
+            res
+        })
+    }
+}
 
 impl<'transaction, T> FromDummy for TableRow<'transaction, T> {
     type Impl = ColumnImpl<Self>;
@@ -0,0 +1,246 @@
+use std::{marker::PhantomData, rc::Rc};
+
+use sea_query::Order;
+
+use super::{operations::Over, Column, DynTypedExpr, IntoColumn, MyTyp};
+
+/// The window frame that includes all rows of the partition.
+const WHOLE_PARTITION: &str = "ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING";
+
+/// This is a combinator function that allows computing sqlite window functions.
+///
+/// For more information refer to [Window];
+pub fn window<'column, S, R>(f: impl FnOnce(&mut Window<'column, S>) -> R) -> R {
+    let mut window = Window {
+        partition: Vec::new(),
+        order: Vec::new(),
+        _p: PhantomData,
+    };
+    f(&mut window)
+}
+
+/// This is the argument type used by the [window] combinator.
+///
+/// Rows are split into partitions with [Window::partition_by] and every partition is ordered
+/// using [Window::order_by_asc] and [Window::order_by_desc].
+/// Window functions like [Window::row_number] are then computed for each row within its partition.
+/// Without any partitions, all rows of the query are in the same partition.
+///
+/// The columns returned by window functions can only be used in the result of the query
+/// and to sort the query. Using them in [crate::Rows::filter] or in an [crate::aggregate] is not allowed by sqlite.
+/// To filter on a window function, compute it in [crate::Rows::join_subquery] instead.
+/// ```
+/// # use rust_query::{migration::{schema, Config}, window, Database, LocalClient, Table};
+/// # #[schema]
+/// # enum Schema {
+/// #     Track { genre: String, name: String, milliseconds: i64 },
+/// # }
+/// # use v0::*;
+/// # let mut client = LocalClient::try_new().unwrap();
+/// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+/// # let mut txn = client.transaction_mut(&database);
+/// # txn.insert(Track { genre: "Rock", name: "Long", milliseconds: 300 });
+/// # txn.insert(Track { genre: "Rock", name: "Short", milliseconds: 100 });
+/// # txn.insert(Track { genre: "Jazz", name: "Solo", milliseconds: 200 });
+/// let ranked = txn.query(|rows| {
+///     let track = Track::join(rows);
+///     let position = window(|w| {
+///         w.partition_by(track.genre());
+///         w.order_by_desc(track.milliseconds());
+///         w.row_number()
+///     });
+///     rows.order_by_asc(track.name());
+///     rows.into_vec((track.name(), position))
+/// });
+/// assert_eq!(ranked, [("Long".to_owned(), 1), ("Short".to_owned(), 2), ("Solo".to_owned(), 1)]);
+/// ```
+pub struct Window<'column, S> {
+    partition: Vec<Rc<DynTypedExpr>>,
+    order: Vec<(Rc<DynTypedExpr>, Order)>,
+    _p: PhantomData<fn(&'column ()) -> S>,
+}
+
+impl<'column, S> Window<'column, S> {
+    /// Add a column to split the rows into partitions.
+    ///
+    /// Rows are in the same partition if they have the same value for all partition columns.
+    pub fn partition_by(&mut self, key: impl IntoColumn<'column, S>) {
+        let key = key.into_column().inner.erase();
+        self.partition.push(Rc::new(key));
+    }
+
+    /// Sort the rows within each partition by `key` in ascending order.
+    ///
+    /// Calling this multiple times will sort by the first key first.
+    pub fn order_by_asc(&mut self, key: impl IntoColumn<'column, S>) {
+        let key = key.into_column().inner.erase();
+        self.order.push((Rc::new(key), Order::Asc));
+    }
+
+    /// Sort the rows within each partition by `key` in descending order.
+    ///
+    /// See [Window::order_by_asc] for more information.
+    pub fn order_by_desc(&mut self, key: impl IntoColumn<'column, S>) {
+        let key = key.into_column().inner.erase();
+        self.order.push((Rc::new(key), Order::Desc));
+    }
+
+    fn over<T: 'static>(
+        &self,
+        name: &'static str,
+        args: Vec<DynTypedExpr>,
+    ) -> Column<'column, S, T> {
+        self.over_frame(name, args, None)
+    }
+
+    fn over_frame<T: 'static>(
+        &self,
+        name: &'static str,
+        args: Vec<DynTypedExpr>,
+        frame: Option<&'static str>,
+    ) -> Column<'column, S, T> {
+        Column::new(Over {
+            name,
+            args,
+            partition: self.partition.clone(),
+            order: self.order.clone(),
+            frame,
+            _p: PhantomData,
+        })
+    }
+
+    /// The number of the row within its partition, starting at 1.
+    ///
+    /// Rows that are equal according to the order get different numbers in an arbitrary order.
+    pub fn row_number(&self) -> Column<'column, S, i64> {
+        self.over("row_number", vec![])
+    }
+
+    /// The rank of the row within its partition, starting at 1.
+    ///
+    /// Rows that are equal according to the order get the same rank.
+    /// The rank of the next row skips the numbers that were shared, e.g. `1, 1, 3`.
+    pub fn rank(&self) -> Column<'column, S, i64> {
+        self.over("rank", vec![])
+    }
+
+    /// The rank of the row within its partition without gaps, starting at 1.
+    ///
+    /// This is like [Window::rank], but the ranks do not skip numbers, e.g. `1, 1, 2`.
+    pub fn dense_rank(&self) -> Column<'column, S, i64> {
+        self.over("dense_rank", vec![])
+    }
+
+    /// The value of a column in the row that is `offset` rows before the current row in its partition.
+    ///
+    /// The result is [None] if there is no such row, which is the case for the first `offset` rows
+    /// of every partition. The previous row can be retrieved with an `offset` of 1.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, window, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Customer { name: String },
+    /// #     Invoice { customer: Customer, day: i64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// # let alice = txn.insert(Customer { name: "Alice" });
+    /// # let bob = txn.insert(Customer { name: "Bob" });
+    /// # txn.insert(Invoice { customer: alice, day: 3 });
+    /// # txn.insert(Invoice { customer: alice, day: 10 });
+    /// # txn.insert(Invoice { customer: bob, day: 5 });
+    /// let previous = txn.query(|rows| {
+    ///     let invoice = Invoice::join(rows);
+    ///     let previous = window(|w| {
+    ///         w.partition_by(invoice.customer());
+    ///         w.order_by_asc(invoice.day());
+    ///         w.lag(invoice.day(), 1)
+    ///     });
+    ///     rows.order_by_asc(invoice.day());
+    ///     rows.into_vec((invoice.day(), previous))
+    /// });
+    /// assert_eq!(previous, [(3, None), (5, None), (10, Some(3))]);
+    /// ```
+    pub fn lag<T: MyTyp>(
+        &self,
+        val: impl IntoColumn<'column, S, Typ = T>,
+        offset: i64,
+    ) -> Column<'column, S, Option<T>> {
+        self.offset("lag", val, offset)
+    }
+
+    /// The value of a column in the row that is `offset` rows after the current row in its partition.
+    ///
+    /// The result is [None] if there is no such row, which is the case for the last `offset` rows
+    /// of every partition. See [Window::lag] for more information.
+    pub fn lead<T: MyTyp>(
+        &self,
+        val: impl IntoColumn<'column, S, Typ = T>,
+        offset: i64,
+    ) -> Column<'column, S, Option<T>> {
+        self.offset("lead", val, offset)
+    }
+
+    fn offset<T: MyTyp>(
+        &self,
+        name: &'static str,
+        val: impl IntoColumn<'column, S, Typ = T>,
+        offset: i64,
+    ) -> Column<'column, S, Option<T>> {
+        assert!(offset >= 0, "offset must not be negative");
+        let val = val.into_column().inner.erase();
+        let offset = IntoColumn::<S>::into_column(offset).inner.erase();
+        self.over(name, vec![val, offset])
+    }
+
+    /// The value of a column in the first row of the partition.
+    ///
+    /// Unlike [Window::lag], this is never [None] because every partition has at least one row.
+    /// ```
+    /// # use rust_query::{migration::{schema, Config}, window, Database, LocalClient, Table};
+    /// # #[schema]
+    /// # enum Schema {
+    /// #     Order { customer: String },
+    /// #     OrderLine { order: Order, position: i64, price: f64 },
+    /// # }
+    /// # use v0::*;
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let database: Database<Schema> = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&database);
+    /// # let order = txn.insert(Order { customer: "Alice" });
+    /// # txn.insert(OrderLine { order, position: 2, price: 5. });
+    /// # txn.insert(OrderLine { order, position: 1, price: 10. });
+    /// let prices = txn.query(|rows| {
+    ///     let line = OrderLine::join(rows);
+    ///     let (first, last) = window(|w| {
+    ///         w.partition_by(line.order());
+    ///         w.order_by_asc(line.position());
+    ///         (w.first_value(line.price()), w.last_value(line.price()))
+    ///     });
+    ///     rows.order_by_asc(line.position());
+    ///     rows.into_vec((line.price(), (first, last)))
+    /// });
+    /// assert_eq!(prices, [(10., (10., 5.)), (5., (10., 5.))]);
+    /// ```
+    pub fn first_value<T: MyTyp>(
+        &self,
+        val: impl IntoColumn<'column, S, Typ = T>,
+    ) -> Column<'column, S, T> {
+        let val = val.into_column().inner.erase();
+        self.over_frame("first_value", vec![val], Some(WHOLE_PARTITION))
+    }
+
+    /// The value of a column in the last row of the partition.
+    ///
+    /// This uses the whole partition, not just the rows up to the current row
+    /// like the sqlite default for ordered windows. See [Window::first_value] for more information.
+    pub fn last_value<T: MyTyp>(
+        &self,
+        val: impl IntoColumn<'column, S, Typ = T>,
+    ) -> Column<'column, S, T> {
+        let val = val.into_column().inner.erase();
+        self.over_frame("last_value", vec![val], Some(WHOLE_PARTITION))
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
     alias::Field,
     ast::MySelect,
     value::{DynTypedExpr, Typed},
-    IntoDummy, IntoColumn, Table,
+    IntoColumn, IntoDummy, Table,
 };
 
 /// this trait is not safe to implement
@@ -50,9 +50,33 @@ impl<'t, S> Reader<'_, 't, S> {
         self.ast.select.push(Box::new((expr, field)))
     }
 
+    pub fn col_or_default<T>(&self, name: &'static str, val: &impl ColumnOrDefault<'t, S, T>) {
+        val.read_col(self, name);
+    }
+
     pub(crate) fn col_erased(&self, name: &'static str, val: DynTypedExpr) {
         let field = Field::Str(name);
         let expr = (val.0)(self.ast.builder());
         self.ast.select.push(Box::new((expr, field)))
     }
 }
+
+/// This trait is implemented for everything that implements [IntoColumn] and for `()`.
+///
+/// It is used for columns that have a default value in the schema.
+/// Using `()` for such a column means that the default value is used when inserting
+/// and that the column is not changed when updating.
+pub trait ColumnOrDefault<'t, S, T> {
+    #[doc(hidden)]
+    fn read_col(&self, f: &Reader<'_, 't, S>, name: &'static str);
+}
+
+impl<'t, S, T, X: IntoColumn<'t, S, Typ = T>> ColumnOrDefault<'t, S, T> for X {
+    fn read_col(&self, f: &Reader<'_, 't, S>, name: &'static str) {
+        f.col(name, self.clone())
+    }
+}
+
+impl<'t, S, T> ColumnOrDefault<'t, S, T> for () {
+    fn read_col(&self, _f: &Reader<'_, 't, S>, _name: &'static str) {}
+}
@@ -0,0 +1,531 @@
+use rust_query::{
+    aggregate, exists,
+    migration::{schema, Config},
+    not_exists, LocalClient, Table, TransactionMut,
+};
+
+#[schema]
+enum Schema {
+    Customer {
+        name: String,
+    },
+    Invoice {
+        customer: Customer,
+        total: f64,
+        note: Option<String>,
+    },
+    Reading {
+        sensor: String,
+        value: Option<f64>,
+    },
+}
+use v0::*;
+
+/// A [Config] can only be opened once per process,
+/// so all tests share this single database.
+#[test]
+fn test_aggregate() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    empty_table(&txn);
+    populate(&mut txn);
+    aggregate_exists(&txn);
+    count(&txn);
+    total(&txn);
+    integer_average(&txn);
+    min_max(&txn);
+    exists_subquery(&txn);
+    percentile(&txn);
+    first_last(&txn);
+    impute(&mut txn);
+    empty_group(&txn);
+    distinct_groups(&txn);
+    group_concat(&txn);
+    filtered(&txn);
+}
+
+fn populate(txn: &mut TransactionMut<Schema>) {
+    let alice = txn.insert(Customer { name: "Alice" });
+    txn.insert(Customer { name: "Bob" });
+    txn.insert(Invoice {
+        customer: alice,
+        total: 10.,
+        note: Some("first"),
+    });
+    txn.insert(Invoice {
+        customer: alice,
+        total: 2.5,
+        note: None::<String>,
+    });
+    txn.insert(Invoice {
+        customer: alice,
+        total: 2.5,
+        note: Some("first"),
+    });
+}
+
+fn empty_table(txn: &TransactionMut<Schema>) {
+    let stats = txn.query_one(aggregate(|rows| {
+        let invoice = Invoice::join(rows);
+        (
+            (rows.sum(invoice.total()), rows.avg(invoice.total())),
+            (
+                rows.count_distinct(invoice.customer()),
+                (rows.exists(), rows.total(invoice.total())),
+            ),
+        )
+    }));
+    assert_eq!(stats, ((0., None), (0, (false, 0.))));
+
+    let any_invoice = txn.query_one(exists(|rows| {
+        Invoice::join(rows);
+    }));
+    assert!(!any_invoice);
+}
+
+fn aggregate_exists(txn: &TransactionMut<Schema>) {
+    let any_invoice = txn.query_one(aggregate(|rows| {
+        Invoice::join(rows);
+        rows.exists()
+    }));
+    assert!(any_invoice);
+
+    let customers = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        let has_invoice = aggregate(|rows| {
+            let invoice = Invoice::join(rows);
+            rows.filter_on(invoice.customer(), &customer);
+            rows.exists()
+        });
+        rows.into_vec((customer.name(), has_invoice))
+    });
+    assert_eq!(
+        customers,
+        [("Alice".to_owned(), true), ("Bob".to_owned(), false)]
+    );
+}
+
+fn count(txn: &TransactionMut<Schema>) {
+    let customers = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        let counts = aggregate(|rows| {
+            let invoice = Invoice::join(rows);
+            rows.filter_on(invoice.customer(), &customer);
+            (
+                rows.count(),
+                (
+                    rows.count_col(invoice.note()),
+                    rows.count_distinct(invoice.total()),
+                ),
+            )
+        });
+        rows.into_vec((customer.name(), counts))
+    });
+    assert_eq!(
+        customers,
+        [
+            ("Alice".to_owned(), (3, (2, 2))),
+            ("Bob".to_owned(), (0, (0, 0)))
+        ]
+    );
+}
+
+fn total(txn: &TransactionMut<Schema>) {
+    let customers = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        let total = aggregate(|rows| {
+            let invoice = Invoice::join(rows);
+            rows.filter_on(invoice.customer(), &customer);
+            rows.total(invoice.total())
+        });
+        rows.into_vec((customer.name(), total))
+    });
+    assert_eq!(
+        customers,
+        [("Alice".to_owned(), 15.), ("Bob".to_owned(), 0.)]
+    );
+
+    // integer columns are summed as floating point numbers
+    let invoices = txn.query_one(aggregate(|rows| {
+        Invoice::join(rows);
+        rows.total(1)
+    }));
+    assert_eq!(invoices, 3.);
+
+    // integer sums stay integers, but they can overflow
+    let count = txn.query_one(aggregate(|rows| {
+        Invoice::join(rows);
+        rows.sum_int(1)
+    }));
+    assert_eq!(count, 3);
+    let overflow = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        txn.query_one(aggregate(|rows| {
+            Invoice::join(rows);
+            rows.sum_int(i64::MAX)
+        }))
+    }));
+    assert!(overflow.is_err());
+    let large = txn.query_one(aggregate(|rows| {
+        Invoice::join(rows);
+        rows.total(i64::MAX)
+    }));
+    assert_eq!(large, 3. * i64::MAX as f64);
+}
+
+fn integer_average(txn: &TransactionMut<Schema>) {
+    let averages = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        let avg = aggregate(|rows| {
+            let invoice = Invoice::join(rows);
+            rows.filter_on(invoice.customer(), &customer);
+            rows.avg_int_scaled(invoice.total().cast_int(), 10)
+        });
+        rows.into_vec((customer.name(), avg))
+    });
+    // (10 + 2 + 2) * 10 / 3 is rounded towards zero
+    assert_eq!(
+        averages,
+        [("Alice".to_owned(), Some(46)), ("Bob".to_owned(), None)]
+    );
+
+    let overflow = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        txn.query_one(aggregate(|rows| {
+            Invoice::join(rows);
+            rows.avg_int_scaled(i64::MAX / 8, 4)
+        }))
+    }));
+    assert!(overflow.is_err());
+}
+
+fn min_max(txn: &TransactionMut<Schema>) {
+    let notes = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        let (min, max) = aggregate(|rows| {
+            let invoice = Invoice::join(rows);
+            rows.filter_on(invoice.customer(), &customer);
+            let note = rows.filter_some(invoice.note());
+            (rows.min(&note), rows.max(note))
+        });
+        rows.into_vec((customer.name(), (min, max)))
+    });
+    assert_eq!(
+        notes,
+        [
+            (
+                "Alice".to_owned(),
+                (Some("first".to_owned()), Some("first".to_owned()))
+            ),
+            ("Bob".to_owned(), (None, None)),
+        ]
+    );
+
+    let (min, max) = txn.query_one(aggregate(|rows| {
+        let customer = Customer::join(rows);
+        (rows.min(customer.name()), rows.max(customer.name()))
+    }));
+    assert_eq!(
+        (min.as_deref(), max.as_deref()),
+        (Some("Alice"), Some("Bob"))
+    );
+
+    let (min, max) = txn.query_one(aggregate(|rows| {
+        let invoice = Invoice::join(rows);
+        (rows.min(invoice.total()), rows.max(invoice.total()))
+    }));
+    assert_eq!((min, max), (Some(2.5), Some(10.)));
+}
+
+fn exists_subquery(txn: &TransactionMut<Schema>) {
+    let any_invoice = txn.query_one(exists(|rows| {
+        Invoice::join(rows);
+    }));
+    assert!(any_invoice);
+
+    let customers = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        let has_small = exists(|rows| {
+            let invoice = Invoice::join(rows);
+            rows.filter_on(invoice.customer(), &customer);
+            rows.filter_on(invoice.total(), 2.5);
+        });
+        rows.into_vec((customer.name(), has_small))
+    });
+    assert_eq!(
+        customers,
+        [("Alice".to_owned(), true), ("Bob".to_owned(), false)]
+    );
+
+    // the same membership test with `IN (SELECT ...)`
+    let customers = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        let has_small = customer.in_query(|rows| {
+            let invoice = Invoice::join(rows);
+            rows.filter(invoice.total().eq(2.5));
+            invoice.customer()
+        });
+        rows.into_vec((customer.name(), has_small))
+    });
+    assert_eq!(
+        customers,
+        [("Alice".to_owned(), true), ("Bob".to_owned(), false)]
+    );
+
+    // the sub-query can return any expression
+    let names = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        rows.filter(customer.name().in_query(|rows| {
+            let invoice = Invoice::join(rows);
+            invoice.customer().name()
+        }));
+        rows.into_vec(customer.name())
+    });
+    assert_eq!(names, ["Alice"]);
+
+    // customers without a small invoice
+    let customers = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        rows.filter(not_exists(|rows| {
+            let invoice = Invoice::join(rows);
+            rows.filter_on(invoice.customer(), &customer);
+            rows.filter_on(invoice.total(), 2.5);
+        }));
+        rows.into_vec(customer.name())
+    });
+    assert_eq!(customers, ["Bob"]);
+}
+
+fn percentile(txn: &TransactionMut<Schema>) {
+    let customers = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        let stats = aggregate(|rows| {
+            let invoice = Invoice::join(rows);
+            rows.filter_on(invoice.customer(), &customer);
+            (
+                (
+                    rows.median(invoice.total()),
+                    rows.percentile(invoice.total(), 0.),
+                ),
+                (
+                    rows.percentile(invoice.total(), 0.75),
+                    rows.percentile(invoice.total(), 1.),
+                ),
+            )
+        });
+        rows.into_vec((customer.name(), stats))
+    });
+    assert_eq!(
+        customers,
+        [
+            (
+                "Alice".to_owned(),
+                ((Some(2.5), Some(2.5)), (Some(6.25), Some(10.)))
+            ),
+            ("Bob".to_owned(), ((None, None), (None, None)))
+        ]
+    );
+
+    let median = txn.query_one(aggregate(|rows| {
+        let invoice = Invoice::join(rows);
+        rows.median(invoice.total())
+    }));
+    assert_eq!(median, Some(2.5));
+}
+
+fn first_last(txn: &TransactionMut<Schema>) {
+    let latest = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        let latest = aggregate(|rows| {
+            let invoice = Invoice::join(rows);
+            rows.filter_on(invoice.customer(), &customer);
+            // invoices are ordered by their id, which is the order of insertion
+            (
+                rows.first(invoice.total(), &invoice),
+                rows.last(invoice.total(), &invoice),
+            )
+        });
+        rows.into_vec((customer.name(), latest))
+    });
+    assert_eq!(
+        latest,
+        [
+            ("Alice".to_owned(), (Some(10.), Some(2.5))),
+            ("Bob".to_owned(), (None, None))
+        ]
+    );
+
+    let biggest_spender = txn.query_one(aggregate(|rows| {
+        let invoice = Invoice::join(rows);
+        rows.last(invoice.customer().name(), invoice.total())
+    }));
+    assert_eq!(biggest_spender.as_deref(), Some("Alice"));
+}
+
+fn impute(txn: &mut TransactionMut<Schema>) {
+    for (sensor, value) in [
+        ("a", Some(1.)),
+        ("a", None),
+        ("a", Some(3.)),
+        ("b", Some(10.)),
+        ("b", None),
+        ("c", None),
+    ] {
+        txn.insert(Reading { sensor, value });
+    }
+
+    // the aggregate is computed for the sensor of each row
+    let values = txn.query(|rows| {
+        let reading = Reading::join(rows);
+        let average = aggregate(|rows| {
+            let other = Reading::join(rows);
+            rows.filter_on(other.sensor(), reading.sensor());
+            let value = rows.filter_some(other.value());
+            rows.avg(value)
+        });
+        rows.into_vec((reading.sensor(), reading.value().or(average)))
+    });
+    assert_eq!(
+        values,
+        [
+            ("a".to_owned(), Some(1.)),
+            ("a".to_owned(), Some(2.)),
+            ("a".to_owned(), Some(3.)),
+            ("b".to_owned(), Some(10.)),
+            ("b".to_owned(), Some(10.)),
+            ("c".to_owned(), None),
+        ]
+    );
+}
+
+fn empty_group(txn: &TransactionMut<Schema>) {
+    // Bob has no invoices, so his group is empty and he is removed by `filter_some`
+    let last_invoice = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        let last = aggregate(|rows| {
+            let invoice = Invoice::join(rows);
+            rows.filter_on(invoice.customer(), &customer);
+            rows.last(&invoice, invoice.total())
+        });
+        let last = rows.filter_some(last);
+        rows.into_vec((customer.name(), (last.total(), last.note())))
+    });
+    assert_eq!(
+        last_invoice,
+        [("Alice".to_owned(), (10., Some("first".to_owned())))]
+    );
+
+    // the group is also empty when all rows are filtered inside the aggregate
+    let max_noted = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        let max = aggregate(|rows| {
+            let invoice = Invoice::join(rows);
+            rows.filter_on(invoice.customer(), &customer);
+            rows.filter(invoice.note().is_some());
+            rows.max(invoice.total())
+        });
+        let max = rows.filter_some(max);
+        rows.into_vec((customer.name(), max))
+    });
+    assert_eq!(max_noted, [("Alice".to_owned(), 10.)]);
+}
+
+fn distinct_groups(txn: &TransactionMut<Schema>) {
+    // group the readings by a computed value instead of a table row
+    let per_group = txn.query(|rows| {
+        let has_value = rows.join_distinct(|rows| {
+            let reading = Reading::join(rows);
+            reading.value().is_some()
+        });
+        let (count, sensors) = aggregate(|rows| {
+            let reading = Reading::join(rows);
+            rows.filter_on(reading.value().is_some(), &has_value);
+            (rows.count(), rows.count_distinct(reading.sensor()))
+        });
+        rows.order_by_asc(&has_value);
+        rows.into_vec((has_value, (count, sensors)))
+    });
+    assert_eq!(per_group, [(false, (3, 3)), (true, (3, 2))]);
+
+    // every row of the outer query is combined with every distinct value
+    let combinations = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        let sensor = rows.join_distinct(|rows| Reading::join(rows).sensor());
+        rows.into_vec((customer.name(), sensor))
+    });
+    assert_eq!(combinations.len(), 2 * 3);
+}
+
+fn group_concat(txn: &TransactionMut<Schema>) {
+    let concatenated = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        let notes = aggregate(|rows| {
+            let invoice = Invoice::join(rows);
+            rows.filter_on(invoice.customer(), &customer);
+            let note = invoice.note().unwrap_or("none");
+            rows.group_concat_ordered(note, ";", invoice.total())
+        });
+        rows.into_vec((customer.name(), notes))
+    });
+    let [(alice, alice_notes), (bob, bob_notes)] = &concatenated[..] else {
+        panic!()
+    };
+    assert_eq!((&**alice, &**bob, &**bob_notes), ("Alice", "Bob", ""));
+    // the two invoices with the same total can be in any order
+    assert!(alice_notes.ends_with(";first"));
+    assert_eq!(alice_notes.matches("first").count(), 2);
+    assert_eq!(alice_notes.matches("none").count(), 1);
+
+    let (unordered, ordered) = txn.query_one(aggregate(|rows| {
+        let reading = Reading::join(rows);
+        (
+            rows.group_concat(reading.sensor(), ""),
+            rows.group_concat_ordered(reading.sensor(), "", reading.sensor()),
+        )
+    }));
+    assert_eq!(unordered.len(), 6);
+    assert_eq!(ordered, "aaabbc");
+}
+
+fn filtered(txn: &TransactionMut<Schema>) {
+    let counts = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        let counts = aggregate(|rows| {
+            let invoice = Invoice::join(rows);
+            rows.filter_on(invoice.customer(), &customer);
+            let (noted, noted_total) = rows.filtered(invoice.note().is_some(), |rows| {
+                (rows.count(), rows.sum(invoice.total()))
+            });
+            let large = rows.filtered(invoice.total().lt(5.).not(), |rows| {
+                // nested conditions are combined
+                let large_noted = rows.filtered(invoice.note().is_some(), |rows| rows.count());
+                (rows.count(), large_noted)
+            });
+            (rows.count(), ((noted, noted_total), large))
+        });
+        rows.into_vec((customer.name(), counts))
+    });
+    assert_eq!(
+        counts,
+        [
+            ("Alice".to_owned(), (3, ((2, 12.5), (1, 1)))),
+            ("Bob".to_owned(), (0, ((0, 0.), (0, 0)))),
+        ]
+    );
+
+    // aggregates that use a sub-query also only use the filtered rows
+    let (first, median) = txn.query_one(aggregate(|rows| {
+        let invoice = Invoice::join(rows);
+        rows.filtered(invoice.note().is_some().not(), |rows| {
+            (
+                rows.first(invoice.total(), invoice.total()),
+                rows.median(invoice.total()),
+            )
+        })
+    }));
+    assert_eq!((first, median), (Some(2.5), Some(2.5)));
+}
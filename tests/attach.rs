@@ -0,0 +1,63 @@
+use rust_query::{
+    migration::{schema, Config},
+    raw_sql, LocalClient, Table,
+};
+
+#[schema]
+enum Schema {
+    Track { name: String, genre: i64 },
+}
+use v0::*;
+
+#[test]
+fn attach() {
+    let dir = std::env::temp_dir().join(format!("rust-query-attach-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("genres.sqlite");
+    rusqlite::Connection::open(&path)
+        .unwrap()
+        .execute_batch(
+            "CREATE TABLE genre (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+            INSERT INTO genre VALUES (1, 'Rock'), (2, 'Jazz');",
+        )
+        .unwrap();
+
+    let mut client = LocalClient::try_new().unwrap();
+    let config = Config::open_in_memory().attach(&path, "genres");
+    let database = client.migrator(config).unwrap().finish().unwrap();
+
+    let mut txn = client.transaction_mut(&database);
+    txn.insert(Track {
+        name: "Nardis",
+        genre: 2,
+    });
+    txn.insert(Track {
+        name: "Money",
+        genre: 1,
+    });
+    txn.insert(Track {
+        name: "Unknown",
+        genre: 3,
+    });
+
+    let res = txn.query(|rows| {
+        let track = Track::join(rows);
+        let genre = raw_sql::<Option<String>, _>(
+            "(SELECT name FROM genres.genre WHERE id = ?)",
+            (track.genre(),),
+        );
+        rows.into_vec((track.name(), genre))
+    });
+    assert_eq!(
+        res,
+        vec![
+            ("Money".to_owned(), Some("Rock".to_owned())),
+            ("Nardis".to_owned(), Some("Jazz".to_owned())),
+            ("Unknown".to_owned(), None),
+        ]
+    );
+
+    drop(txn);
+    drop(database);
+    std::fs::remove_dir_all(dir).unwrap();
+}
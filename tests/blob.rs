@@ -0,0 +1,62 @@
+use rust_query::{
+    migration::{schema, Config},
+    LocalClient,
+};
+
+#[schema]
+enum Schema {
+    #[unique(key)]
+    ApiKey {
+        #[blob_len(4)]
+        key: Vec<u8>,
+        #[blob_len(2)]
+        salt: Option<Vec<u8>>,
+        note: Vec<u8>,
+    },
+}
+use v0::*;
+
+#[test]
+fn blob() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    let key = txn
+        .try_insert(ApiKey {
+            key: [1, 2, 3, 4].as_slice(),
+            salt: None::<Vec<u8>>,
+            note: vec![],
+        })
+        .unwrap();
+    assert_eq!(txn.query_one(key.key()), [1, 2, 3, 4]);
+    assert_eq!(txn.query_one(key.note()), Vec::<u8>::new());
+
+    let found = txn.query_one(ApiKey::unique(vec![1, 2, 3, 4]));
+    assert_eq!(found, Some(key));
+
+    txn.try_update(
+        key,
+        ApiKey {
+            key: key.key(),
+            salt: Some(vec![5, 6]),
+            note: b"renamed".as_slice(),
+        },
+    )
+    .unwrap();
+    assert_eq!(txn.query_one(key.salt()), Some(vec![5, 6]));
+
+    // blobs are ordered byte-wise
+    assert!(txn.query_one(key.key().lt([1, 2, 4].as_slice())));
+    assert!(!txn.query_one(key.key().lt([1, 2, 3].as_slice())));
+
+    // the length is checked by sqlite
+    let mut txn = txn.downgrade();
+    let sql = "INSERT INTO api_key (key, note) VALUES (x'050607', x'')";
+    let err = txn.unchecked_transaction().execute(sql, []).unwrap_err();
+    assert!(err.to_string().contains("CHECK constraint failed"));
+}
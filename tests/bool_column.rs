@@ -0,0 +1,129 @@
+use rust_query::{
+    migration::{schema, Alter, Config},
+    Database, LocalClient, Table, TransactionMut,
+};
+
+#[schema]
+#[version(0..=1)]
+enum Schema {
+    Task {
+        title: String,
+        #[version(..1)]
+        done: i64,
+        #[version(1..)]
+        done: bool,
+        #[version(1..)]
+        archived: Option<bool>,
+    },
+}
+use v1::*;
+
+fn migrate(client: &mut LocalClient) -> Database<Schema> {
+    let config = Config::open_in_memory()
+        .init_stmt("INSERT INTO task (title, done) VALUES ('laundry', 1), ('dishes', 0)");
+    let m = client.migrator(config).unwrap();
+    let m = m.migrate(update::Schema {
+        task: Box::new(|task| {
+            Alter::new(update::TaskMigration {
+                done: task.done().eq(1),
+                archived: None::<bool>,
+            })
+        }),
+    });
+    m.finish().unwrap()
+}
+
+/// Migrate an `i64` column to a `bool` column and check that only 0 and 1 are allowed.
+#[test]
+fn bool_column() {
+    let mut client = LocalClient::try_new().unwrap();
+    let db = migrate(&mut client);
+    let mut txn = client.transaction_mut(&db);
+
+    txn.insert(Task {
+        title: "groceries",
+        done: false,
+        archived: Some(true),
+    });
+
+    let tasks = txn.query(|rows| {
+        let task = Task::join(rows);
+        rows.filter(task.archived().is_some().not());
+        rows.into_vec((task.title(), task.done()))
+    });
+    assert_eq!(
+        tasks,
+        [("dishes".to_owned(), false), ("laundry".to_owned(), true)]
+    );
+
+    let archived = txn.query(|rows| {
+        let task = Task::join(rows);
+        let archived = rows.filter_some(task.archived());
+        rows.filter(archived.and(task.done().not()));
+        rows.into_vec(task.title())
+    });
+    assert_eq!(archived, ["groceries"]);
+
+    filter_eq(&txn);
+    update(&mut txn);
+
+    let mut txn = txn.downgrade();
+    let raw = txn.unchecked_transaction();
+    raw.execute("UPDATE task SET done = 2", [])
+        .expect_err("check constraint only allows 0 and 1");
+    raw.execute("UPDATE task SET archived = 2", [])
+        .expect_err("check constraint also applies to optional columns");
+}
+
+fn filter_eq(txn: &TransactionMut<Schema>) {
+    let done = |value: bool| {
+        txn.query(|rows| {
+            let task = Task::join(rows);
+            rows.filter(task.done().eq(value));
+            rows.into_vec(task.title())
+        })
+    };
+    assert_eq!(done(true), ["laundry"]);
+    assert_eq!(done(false), ["dishes", "groceries"]);
+
+    let not_archived = txn.query(|rows| {
+        let task = Task::join(rows);
+        rows.filter(task.archived().unwrap_or(false).not());
+        rows.into_vec(task.title())
+    });
+    assert_eq!(not_archived, ["dishes", "laundry"]);
+}
+
+fn update(txn: &mut TransactionMut<Schema>) {
+    let dishes = txn.query(|rows| {
+        let task = Task::join(rows);
+        rows.filter(task.title().eq("dishes"));
+        rows.into_vec(task)
+    })[0];
+
+    txn.update(
+        dishes,
+        Task {
+            title: dishes.title(),
+            done: true,
+            archived: Some(false),
+        },
+    );
+    assert_eq!(
+        txn.query_one((dishes.done(), dishes.archived())),
+        (true, Some(false))
+    );
+
+    txn.update(
+        dishes,
+        Task {
+            title: dishes.title(),
+            done: dishes.done().not(),
+            archived: None::<bool>,
+        },
+    );
+    assert_eq!(
+        txn.query_one((dishes.done(), dishes.archived())),
+        (false, None)
+    );
+}
@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+use rust_query::{
+    migration::{schema, Config},
+    LocalClient,
+};
+
+#[schema]
+enum Schema {
+    Counter { value: i64 },
+}
+use v0::*;
+
+#[test]
+fn busy_timeout() {
+    let mut client = LocalClient::try_new().unwrap();
+    let config = Config::open_in_memory().busy_timeout(Duration::from_millis(250));
+    let database = client.migrator::<Schema>(config).unwrap().finish().unwrap();
+    let mut txn = client.transaction_mut(&database).downgrade();
+
+    let conn = txn.unchecked_transaction();
+    let timeout: i64 = conn
+        .pragma_query_value(None, "busy_timeout", |row| row.get(0))
+        .unwrap();
+    assert_eq!(timeout, 250);
+}
@@ -5,7 +5,8 @@ use std::fmt::Debug;
 use chinook_schema::*;
 use expect_test::expect_file;
 use rust_query::{
-    aggregate, Column, Dummy, IntoColumn, IntoDummy, LocalClient, Table, TableRow, Transaction,
+    aggregate, not_exists, Column, Dummy, IntoColumn, IntoDummy, LocalClient, Table, TableRow,
+    Transaction,
 };
 
 /// requires [PartialEq] to get rid of unused warnings.
@@ -40,6 +41,7 @@ fn test_queries() {
     assert_dbg(&res[..], "the_artists");
     let res = ten_space_tracks(&db);
     assert_dbg(&res[..], "ten_space_tracks");
+    artists_without_album(&db);
 
     free_reference(&db);
 
@@ -274,6 +276,30 @@ fn get_the_artists(db: &Transaction<Schema>) -> Vec<String> {
     })
 }
 
+/// The anti-join gives the same artists as counting their albums.
+fn artists_without_album(db: &Transaction<Schema>) {
+    let anti_join = db.query(|rows| {
+        let artist = Artist::join(rows);
+        rows.filter(not_exists(|rows| {
+            let album = Album::join(rows);
+            rows.filter_on(album.artist(), &artist);
+        }));
+        rows.into_vec(artist.name())
+    });
+    let counted = db.query(|rows| {
+        let artist = Artist::join(rows);
+        let albums = aggregate(|rows| {
+            let album = Album::join(rows);
+            rows.filter_on(album.artist(), &artist);
+            rows.count()
+        });
+        rows.filter(albums.eq(0));
+        rows.into_vec(artist.name())
+    });
+    assert!(!anti_join.is_empty());
+    assert_eq!(anti_join, counted);
+}
+
 fn ten_space_tracks(db: &Transaction<Schema>) -> Vec<String> {
     db.query(|rows| {
         let track = Track::join(rows);
@@ -191,4 +191,27 @@ mod tests {
         expect!["a57e97b8c243859a"].assert_eq(&hash_schema::<v0::Schema>());
         expect!["15e9ff46816e4b45"].assert_eq(&hash_schema::<v1::Schema>());
     }
+
+    #[test]
+    #[cfg(feature = "dev")]
+    fn schema_diff() {
+        use rust_query::migration::diff_schema;
+
+        let changes: Vec<_> = diff_schema::<v1::Schema, v2::Schema>()
+            .iter()
+            .map(|change| change.to_string())
+            .collect();
+        expect![[r#"
+            [
+                "change column `phone` of table `customer`",
+                "add column `extra` to table `genre_new`",
+                "drop table `media_type`",
+                "change column `media_type` of table `track`",
+                "add column `byte_price` to table `track`",
+                "add column `composer_table` to table `track`",
+                "create table `composer`",
+            ]
+        "#]]
+        .assert_debug_eq(&changes);
+    }
 }
@@ -0,0 +1,41 @@
+use rust_query::{Database, LocalClient, Table};
+use rust_query_macros::schema;
+
+#[schema]
+enum Schema {
+    MyTable { name: String },
+}
+use v0::*;
+
+fn other_transaction(db: Database<Schema>) {
+    let mut client = LocalClient::try_new().unwrap();
+
+    let txn = client.transaction(&db);
+    let items = txn.query(|rows| {
+        let item = MyTable::join(rows);
+        rows.into_vec(item)
+    });
+    drop(txn);
+
+    let txn = client.transaction(&db);
+    for item in items {
+        let name = txn.query_one(item.name());
+        println!("{name}")
+    }
+}
+
+fn other_thread(db: Database<Schema>) {
+    let mut client = LocalClient::try_new().unwrap();
+
+    let txn = client.transaction(&db);
+    let items = txn.query(|rows| {
+        let item = MyTable::join(rows);
+        rows.into_vec(item)
+    });
+
+    std::thread::spawn(move || {
+        println!("{items:?}");
+    });
+}
+
+fn main() {}
@@ -0,0 +1,78 @@
+#![cfg(feature = "chrono")]
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use rust_query::{
+    migration::{schema, Config},
+    LocalClient, Table, UtcNow,
+};
+
+#[schema]
+enum Schema {
+    Event {
+        name: String,
+        starts: chrono::NaiveDateTime,
+        #[default(rust_query::UtcNow)]
+        created: chrono::DateTime<chrono::Utc>,
+        cancelled: Option<chrono::DateTime<chrono::Utc>>,
+    },
+}
+use v0::*;
+
+fn date(day: u32, hour: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 3, day)
+        .unwrap()
+        .and_hms_milli_opt(hour, 30, 0, 250)
+        .unwrap()
+}
+
+#[test]
+fn datetime() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    let before = Utc::now() - Duration::seconds(1);
+    let cancelled: DateTime<Utc> = date(1, 8).and_utc();
+    let party = txn.insert(Event {
+        name: "party",
+        starts: date(10, 20),
+        created: UtcNow,
+        cancelled: None::<DateTime<Utc>>,
+    });
+    txn.insert(Event {
+        name: "breakfast",
+        starts: date(10, 8),
+        created: UtcNow,
+        cancelled: Some(cancelled),
+    });
+    txn.insert(Event {
+        name: "meeting",
+        starts: date(2, 9),
+        created: UtcNow,
+        cancelled: None::<DateTime<Utc>>,
+    });
+
+    assert_eq!(txn.query_one(party.starts()), date(10, 20));
+    assert!(txn.query_one(party.created()) > before);
+
+    // text order is the same as datetime order
+    let order = txn.query(|rows| {
+        let event = Event::join(rows);
+        rows.filter(event.starts().lt(date(10, 12)));
+        rows.order_by_asc(event.starts());
+        rows.into_vec(event.name())
+    });
+    assert_eq!(order, ["meeting", "breakfast"]);
+
+    let cancelled_events = txn.query(|rows| {
+        let event = Event::join(rows);
+        let at = rows.filter_some(event.cancelled());
+        rows.filter(at.eq(cancelled));
+        rows.into_vec((event.name(), at))
+    });
+    assert_eq!(cancelled_events, [("breakfast".to_owned(), cancelled)]);
+}
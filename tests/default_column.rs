@@ -0,0 +1,103 @@
+use rust_query::{
+    migration::{schema, Alter, Config},
+    Database, IntoColumn, IntoDummy, LocalClient, Table, UnixEpoch,
+};
+
+#[schema]
+#[version(0..=1)]
+enum Schema {
+    Post {
+        title: String,
+        #[default(0)]
+        views: i64,
+        #[default("draft")]
+        status: String,
+        #[version(1..)]
+        #[default(rust_query::UnixEpoch)]
+        created: i64,
+    },
+    Counter {
+        #[default(0)]
+        count: i64,
+    },
+}
+use v1::*;
+
+fn migrate(client: &mut LocalClient) -> Database<Schema> {
+    let config = Config::open_in_memory().init_stmt("INSERT INTO post (title) VALUES ('old')");
+    let m = client.migrator(config).unwrap();
+    let m = m.migrate(update::Schema {
+        post: Box::new(|_post| Alter::new(update::PostMigration { created: 0 })),
+    });
+    m.finish().unwrap()
+}
+
+/// Columns with a default value can be set to `()` to use the default value.
+#[test]
+fn default_column() {
+    let mut client = LocalClient::try_new().unwrap();
+    let db = migrate(&mut client);
+    let mut txn = client.transaction_mut(&db);
+
+    let before = txn.query_one(UnixEpoch.into_column());
+    let new = txn.insert(Post {
+        title: "new",
+        views: (),
+        status: (),
+        created: (),
+    });
+    let published = txn.insert(Post {
+        title: "published",
+        views: 10,
+        status: "published",
+        created: 1,
+    });
+
+    let posts = txn.query(|rows| {
+        let post = Post::join(rows);
+        rows.into_vec((post.title(), (post.views(), post.status())))
+    });
+    assert_eq!(
+        posts,
+        [
+            ("new".to_owned(), (0, "draft".to_owned())),
+            ("old".to_owned(), (0, "draft".to_owned())),
+            ("published".to_owned(), (10, "published".to_owned())),
+        ]
+    );
+    assert!(txn.query_one(new.created()) >= before);
+    assert_eq!(txn.query_one(published.created()), 1);
+
+    // `()` keeps the current value when updating
+    txn.update(
+        published,
+        Post {
+            title: "updated",
+            views: 11,
+            status: (),
+            created: (),
+        },
+    );
+    assert_eq!(
+        txn.query_one((published.title(), (published.views(), published.status()))),
+        ("updated".to_owned(), (11, "published".to_owned()))
+    );
+
+    // all columns can use their default value
+    let counter = txn.insert(Counter { count: () });
+    assert_eq!(txn.query_one(counter.count()), 0);
+    txn.update(counter, Counter { count: () });
+    assert_eq!(txn.query_one(counter.count()), 0);
+
+    // the default values can be retrieved right after inserting
+    let (views, status) = txn.insert_returning(
+        Post {
+            title: "returning",
+            views: (),
+            status: (),
+            created: (),
+        },
+        |post| (post.views(), post.status()).into_dummy(),
+    );
+    assert_eq!((views, status), (0, "draft".to_owned()));
+}
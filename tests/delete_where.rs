@@ -0,0 +1,72 @@
+use rust_query::{
+    migration::{schema, Config},
+    LocalClient, Table, TransactionWeak,
+};
+
+#[schema]
+enum Schema {
+    Customer {
+        name: String,
+    },
+    #[no_reference]
+    Invoice {
+        customer: Customer,
+        total: f64,
+    },
+}
+use v0::*;
+
+fn count_rows(txn: &mut TransactionWeak<Schema>) -> (i64, i64) {
+    let sql = "SELECT (SELECT count(*) FROM customer), (SELECT count(*) FROM invoice)";
+    let conn = txn.unchecked_transaction();
+    conn.query_row(sql, [], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+}
+
+#[test]
+fn delete_where() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    let alice = txn.insert(Customer { name: "Alice" });
+    let bob = txn.insert(Customer { name: "Bob" });
+    for (customer, total) in [(alice, 10.), (alice, 5.), (bob, 7.5)] {
+        txn.insert(Invoice { customer, total });
+    }
+    let mut txn = txn.downgrade();
+    assert_eq!(count_rows(&mut txn), (2, 3));
+
+    // customers are still referenced by invoices, so nothing is deleted
+    let res = txn.delete_where(Customer::join);
+    assert_eq!(res, Err(()));
+    assert_eq!(count_rows(&mut txn), (2, 3));
+
+    let res = txn.delete_where(|rows| {
+        let invoice = Invoice::join(rows);
+        rows.filter(invoice.customer().name().eq("Alice"));
+        invoice
+    });
+    assert_eq!(res, Ok(2));
+    assert_eq!(count_rows(&mut txn), (2, 1));
+
+    // nothing matches anymore
+    let res = txn.delete_where(|rows| {
+        let invoice = Invoice::join(rows);
+        rows.filter(invoice.customer().name().eq("Alice"));
+        invoice
+    });
+    assert_eq!(res, Ok(0));
+
+    let res = txn.delete_where(|rows| {
+        let customer = Customer::join(rows);
+        rows.filter(customer.name().eq("Alice"));
+        customer
+    });
+    assert_eq!(res, Ok(1));
+    assert_eq!(count_rows(&mut txn), (1, 1));
+}
@@ -0,0 +1,53 @@
+use rust_query::{
+    migration::{schema, Alter, Config},
+    raw_sql, LocalClient, Table,
+};
+
+#[schema]
+#[version(0..=1)]
+enum Schema {
+    User {
+        name: String,
+        #[version(..1)]
+        age: i64,
+        #[version(..1)]
+        nickname: Option<String>,
+    },
+    Account {
+        owner: User,
+        #[version(..1)]
+        #[unique]
+        email: String,
+    },
+}
+use v1::*;
+
+/// Columns that are only removed are dropped without rebuilding the table.
+#[test]
+fn drop_column() {
+    let mut client = LocalClient::try_new().unwrap();
+    let config = Config::open_in_memory().init_stmt(
+        "INSERT INTO user (name, age, nickname) VALUES ('alice', 30, 'al');
+        INSERT INTO account (owner, email) VALUES (1, 'alice@example.com');",
+    );
+    let m = client.migrator(config).unwrap();
+    // `user` does not need a migration, but `account` does because `email` is unique
+    let m = m.migrate(update::Schema {
+        account: Box::new(|_account| Alter::new(update::AccountMigration {})),
+    });
+    let db = m.finish().unwrap();
+
+    let txn = client.transaction(&db);
+    let columns = |table: &str| {
+        let sql = format!("(SELECT group_concat(name) FROM pragma_table_info('{table}'))");
+        txn.query_one(raw_sql::<String, _>(&sql, ()))
+    };
+    assert_eq!(columns("user"), "name,id");
+    assert_eq!(columns("account"), "owner,id");
+
+    let users = txn.query(|rows| {
+        let account = Account::join(rows);
+        rows.into_vec(account.owner().name())
+    });
+    assert_eq!(users, ["alice"]);
+}
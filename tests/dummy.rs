@@ -0,0 +1,206 @@
+use rust_query::{
+    migration::{schema, Config},
+    Dummy, IntoColumn, LocalClient, Table, TableRow, TransactionMut,
+};
+
+#[schema]
+enum Schema {
+    Customer {
+        name: String,
+        address: String,
+        city: String,
+    },
+    Employee {
+        title: String,
+        address: String,
+        city: String,
+    },
+    Invoice {
+        customer: Customer,
+        support_rep: Employee,
+        total: f64,
+    },
+}
+use v0::*;
+
+#[test]
+fn test_dummy() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    flatten(&mut txn);
+    rename(&mut txn);
+    generic(&txn);
+    duplicate_columns(&txn);
+}
+
+#[derive(Dummy, Debug, PartialEq)]
+#[rust_query(From = Customer, From = Employee)]
+struct Address {
+    address: String,
+    city: String,
+}
+
+#[derive(Dummy, Debug, PartialEq)]
+#[rust_query(From = Customer)]
+struct CustomerInfo {
+    name: String,
+    #[rust_query(flatten)]
+    location: Address,
+}
+
+#[derive(Dummy, Debug, PartialEq)]
+#[rust_query(From = Employee)]
+struct EmployeeInfo {
+    title: String,
+    #[rust_query(flatten)]
+    location: Address,
+}
+
+#[derive(Dummy, Debug, PartialEq)]
+#[rust_query(From = Invoice, lt = 't)]
+struct InvoiceInfo<'t> {
+    customer: TableRow<'t, Customer>,
+    #[rust_query(rename = "support_rep")]
+    rep: TableRow<'t, Employee>,
+    #[rust_query(rename = "total")]
+    amount: f64,
+}
+
+#[derive(Dummy, Debug, PartialEq)]
+struct Labeled<T, U = ()>
+where
+    U: std::fmt::Debug,
+{
+    label: String,
+    value: T,
+    extra: U,
+}
+
+fn flatten(txn: &mut TransactionMut<Schema>) {
+    let customer = txn.insert(Customer {
+        name: "Alice",
+        address: "Main Street 1",
+        city: "Springfield",
+    });
+    let employee = txn.insert(Employee {
+        title: "Manager",
+        address: "Side Street 2",
+        city: "Shelbyville",
+    });
+
+    assert_eq!(
+        txn.query_one(customer.into_trivial::<CustomerInfo>()),
+        CustomerInfo {
+            name: "Alice".to_owned(),
+            location: Address {
+                address: "Main Street 1".to_owned(),
+                city: "Springfield".to_owned(),
+            },
+        }
+    );
+    assert_eq!(
+        txn.query_one(employee.into_trivial::<EmployeeInfo>()),
+        EmployeeInfo {
+            title: "Manager".to_owned(),
+            location: Address {
+                address: "Side Street 2".to_owned(),
+                city: "Shelbyville".to_owned(),
+            },
+        }
+    );
+}
+
+fn rename(txn: &mut TransactionMut<Schema>) {
+    let customer = txn.insert(Customer {
+        name: "Bob",
+        address: "Main Street 2",
+        city: "Springfield",
+    });
+    let rep = txn.insert(Employee {
+        title: "Support",
+        address: "Side Street 3",
+        city: "Shelbyville",
+    });
+    let invoice = txn.insert(Invoice {
+        customer,
+        support_rep: rep,
+        total: 12.5,
+    });
+
+    assert_eq!(
+        txn.query_one(invoice.into_trivial::<InvoiceInfo>()),
+        InvoiceInfo {
+            customer,
+            rep,
+            amount: 12.5,
+        }
+    );
+}
+
+fn generic(txn: &TransactionMut<Schema>) {
+    let cities = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        rows.into_vec(LabeledDummy {
+            label: customer.name(),
+            value: customer.city(),
+            extra: (),
+        })
+    });
+    assert_eq!(
+        cities[0],
+        Labeled {
+            label: "Alice".to_owned(),
+            value: "Springfield".to_owned(),
+            extra: (),
+        }
+    );
+
+    let totals = txn.query(|rows| {
+        let invoice = Invoice::join(rows);
+        rows.into_vec(LabeledDummy {
+            label: invoice.customer().name(),
+            value: invoice.total(),
+            extra: invoice.support_rep().into_trivial::<EmployeeInfo>(),
+        })
+    });
+    assert_eq!(totals[0].value, 12.5);
+    assert_eq!(totals[0].extra.title, "Support");
+}
+
+fn duplicate_columns(txn: &TransactionMut<Schema>) {
+    // fields that use the same column share a single column in the query
+    let (sql, _) = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        rows.to_sql(LabeledDummy {
+            label: customer.name(),
+            value: customer.city(),
+            extra: (customer.name(), customer.city()),
+        })
+    });
+    assert_eq!(sql.matches("\"name\" AS \"").count(), 1);
+    assert_eq!(sql.matches("\"city\" AS \"").count(), 1);
+
+    let names = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        rows.filter(customer.name().eq("Alice"));
+        rows.into_vec(LabeledDummy {
+            label: customer.name(),
+            value: customer.city(),
+            extra: (customer.name(), customer.city()),
+        })
+    });
+    assert_eq!(
+        names,
+        [Labeled {
+            label: "Alice".to_owned(),
+            value: "Springfield".to_owned(),
+            extra: ("Alice".to_owned(), "Springfield".to_owned()),
+        }]
+    );
+}
@@ -0,0 +1,335 @@
+use std::{panic::AssertUnwindSafe, time::Duration};
+
+use rust_query::{
+    call_fn, concat_ws, if_then,
+    migration::{schema, Config},
+    optional, raw_sql, IntoColumn, IntoDummy, LocalClient, Table, TableRow, TransactionMut,
+};
+
+#[schema]
+enum Schema {
+    Genre {
+        name: String,
+    },
+    Artist {
+        name: String,
+    },
+    Album {
+        title: String,
+        artist: Option<Artist>,
+    },
+    Track {
+        name: String,
+        genre: Genre,
+        album: Option<Album>,
+        milliseconds: i64,
+        composer: Option<String>,
+    },
+}
+use v0::*;
+
+#[test]
+fn test_expressions() {
+    let mut client = LocalClient::try_new().unwrap();
+    let config = Config::open_in_memory()
+        .scalar_function("minutes", 1, |ctx| Ok(ctx.get::<i64>(0)? / 60_000))
+        .scalar_function("answer", 0, |_ctx| Ok(42));
+    let database = client.migrator(config).unwrap().finish().unwrap();
+    let mut txn = client.transaction_mut(&database);
+    populate(&mut txn);
+
+    in_values(&txn);
+    case_when(&txn);
+    rounding(&txn);
+    math(&txn);
+    flatten(&txn);
+    set_operations(&txn);
+    duration(&txn);
+    custom_function(&txn);
+    raw(&txn);
+    like(&txn);
+    concat(&txn);
+}
+
+fn populate(txn: &mut TransactionMut<Schema>) {
+    let rock = txn.insert(Genre { name: "Rock" });
+    let jazz = txn.insert(Genre { name: "Jazz" });
+    let metal = txn.insert(Genre { name: "Metal" });
+
+    let miles = txn.insert(Artist {
+        name: "Miles Davis",
+    });
+    let kind_of_blue = txn.insert(Album {
+        title: "Kind of Blue",
+        artist: Some(miles),
+    });
+    let paranoid = txn.insert(Album {
+        title: "Paranoid",
+        artist: None::<TableRow<Artist>>,
+    });
+
+    txn.insert(Track {
+        name: "Highway",
+        genre: rock,
+        album: None::<TableRow<Album>>,
+        milliseconds: 200_000,
+        composer: Some("AC/DC"),
+    });
+    txn.insert(Track {
+        name: "So What",
+        genre: jazz,
+        album: Some(kind_of_blue),
+        milliseconds: 545_000,
+        composer: Some("Miles Davis"),
+    });
+    txn.insert(Track {
+        name: "Paranoid",
+        genre: metal,
+        album: Some(paranoid),
+        milliseconds: 170_000,
+        composer: None::<String>,
+    });
+}
+
+fn in_values(txn: &TransactionMut<Schema>) {
+    let names = |genres: Vec<&str>| {
+        txn.query(|rows| {
+            let track = Track::join(rows);
+            rows.filter(track.genre().name().in_values(genres));
+            rows.into_vec(track.name())
+        })
+    };
+    assert_eq!(names(vec!["Jazz", "Metal"]), ["Paranoid", "So What"]);
+    assert_eq!(names(vec!["Jazz"]), ["So What"]);
+    assert_eq!(names(vec![]), Vec::<String>::new());
+}
+
+fn case_when(txn: &TransactionMut<Schema>) {
+    let lengths = txn.query(|rows| {
+        let track = Track::join(rows);
+        let length = if_then(
+            track.milliseconds().lt(180_000),
+            "short",
+            if_then(track.milliseconds().lt(300_000), "medium", "long"),
+        );
+        rows.into_vec((track.name(), length))
+    });
+    assert_eq!(
+        lengths,
+        [
+            ("Highway".to_owned(), "medium".to_owned()),
+            ("Paranoid".to_owned(), "short".to_owned()),
+            ("So What".to_owned(), "long".to_owned()),
+        ]
+    );
+}
+
+fn rounding(txn: &TransactionMut<Schema>) {
+    let round = |x: f64| txn.query_one(x.into_column().round(2));
+    assert_eq!(round(12.345678), 12.35);
+    assert_eq!(round(-0.125), -0.13);
+
+    let floor_ceil = |x: f64| {
+        let x = x.into_column();
+        txn.query_one((x.floor(), x.ceil()))
+    };
+    assert_eq!(floor_ceil(2.5), (2., 3.));
+    assert_eq!(floor_ceil(-2.5), (-3., -2.));
+    assert_eq!(floor_ceil(7.), (7., 7.));
+    assert_eq!(floor_ceil(1e300), (1e300, 1e300));
+}
+
+fn math(txn: &TransactionMut<Schema>) {
+    let distance = |x: f64, y: f64| {
+        let (x, y) = (x.into_column(), y.into_column());
+        let sum = x.pow(2.).unwrap_or(0.).add(y.pow(2.).unwrap_or(0.));
+        txn.query_one(sum.sqrt())
+    };
+
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    if conn.prepare("SELECT sqrt(1.0)").is_err() {
+        let err = std::panic::catch_unwind(AssertUnwindSafe(|| distance(3., 4.))).unwrap_err();
+        let msg = err.downcast_ref::<String>().unwrap();
+        assert!(msg.contains("SQLITE_ENABLE_MATH_FUNCTIONS"), "{msg}");
+        return;
+    }
+    assert_eq!(distance(3., 4.), Some(5.));
+    assert_eq!(txn.query_one((-1.).into_column().sqrt()), None);
+}
+
+fn flatten(txn: &TransactionMut<Schema>) {
+    let artists = txn.query(|rows| {
+        let track = Track::join(rows);
+        let artist = optional(|row| {
+            let album = row.and(track.album());
+            row.then(album.artist())
+        });
+        let name = optional(|row| {
+            let artist = row.and(artist.flatten());
+            row.then(artist.name())
+        });
+        rows.into_vec((track.name(), name))
+    });
+    assert_eq!(
+        artists,
+        [
+            ("Highway".to_owned(), None),
+            ("Paranoid".to_owned(), None),
+            ("So What".to_owned(), Some("Miles Davis".to_owned())),
+        ]
+    );
+
+    let titles = txn.query(|rows| {
+        let track = Track::join(rows);
+        let title = optional(|row| {
+            let album = row.and(track.album());
+            row.then_or(album.title(), "Single")
+        });
+        rows.into_vec(title)
+    });
+    assert_eq!(titles, ["Kind of Blue", "Paranoid", "Single"]);
+}
+
+fn set_operations(txn: &TransactionMut<Schema>) {
+    // genres with a track longer than 3 minutes: Jazz, Rock
+    // genres with an "a" in the name: Jazz, Metal
+    let (both, only_long) = txn.query(|rows| {
+        let track = Track::join(rows);
+        rows.filter(track.milliseconds().lt(180_000).not());
+        let genre = track.genre().name();
+        let both = rows.into_vec_intersect(&genre, |rows| {
+            let genre = Genre::join(rows);
+            rows.filter(genre.name().contains("a"));
+            genre.name().into_dummy()
+        });
+        let only_long = rows.into_vec_except(&genre, |rows| {
+            let genre = Genre::join(rows);
+            rows.filter(genre.name().contains("a"));
+            genre.name().into_dummy()
+        });
+        let none = rows.into_vec_except((&genre, track.name()), |rows| {
+            let track = Track::join(rows);
+            (track.genre().name(), track.name()).into_dummy()
+        });
+        assert_eq!(none, []);
+        (both, only_long)
+    });
+    assert_eq!(both, ["Jazz"]);
+    assert_eq!(only_long, ["Rock"]);
+
+    let (either, all) = txn.query(|rows| {
+        let track = Track::join(rows);
+        rows.filter(track.milliseconds().lt(180_000).not());
+        let genre = track.genre().name();
+        let either = rows.into_vec_union(&genre, |rows| {
+            let genre = Genre::join(rows);
+            rows.filter(genre.name().contains("a"));
+            genre.name().into_dummy()
+        });
+        let all = rows.into_vec_union_all(&genre, |rows| {
+            let genre = Genre::join(rows);
+            rows.filter(genre.name().contains("a"));
+            genre.name().into_dummy()
+        });
+        (either, all)
+    });
+    assert_eq!(either, ["Jazz", "Metal", "Rock"]);
+    assert_eq!(all, ["Jazz", "Jazz", "Metal", "Rock"]);
+}
+
+fn duration(txn: &TransactionMut<Schema>) {
+    let short = txn.query(|rows| {
+        let track = Track::join(rows);
+        rows.filter(track.milliseconds().lt(Duration::from_secs(300)));
+        rows.into_vec((
+            track.name(),
+            track.milliseconds().into_trivial::<Duration>(),
+        ))
+    });
+    assert_eq!(
+        short,
+        [
+            ("Highway".to_owned(), Duration::from_secs(200)),
+            ("Paranoid".to_owned(), Duration::from_secs(170))
+        ]
+    );
+
+    // precision below one millisecond is truncated
+    let truncated = Duration::from_micros(1_999).into_column();
+    let truncated = txn.query_one(truncated.into_trivial::<Duration>());
+    assert_eq!(truncated, Duration::from_millis(1));
+}
+
+fn custom_function(txn: &TransactionMut<Schema>) {
+    let long = txn.query(|rows| {
+        let track = Track::join(rows);
+        let minutes = call_fn::<i64, _>("minutes", (track.milliseconds(),));
+        rows.filter(minutes.lt(5).not());
+        rows.into_vec((track.name(), minutes))
+    });
+    assert_eq!(long, [("So What".to_owned(), 9)]);
+
+    let answer = txn.query_one(call_fn::<i64, _>("answer", ()));
+    assert_eq!(answer, 42);
+}
+
+fn raw(txn: &TransactionMut<Schema>) {
+    let names = txn.query(|rows| {
+        let track = Track::join(rows);
+        // the result is wrapped in parentheses, so it can be used with other operators
+        let seconds = raw_sql::<i64, _>("? / 1000 + ?", (track.milliseconds(), 1));
+        rows.filter(seconds.lt(200));
+        rows.into_vec((track.name(), seconds))
+    });
+    assert_eq!(names, [("Paranoid".to_owned(), 171)]);
+
+    let literal = txn.query_one(raw_sql::<String, _>("'?' || ?", ("!",)));
+    assert_eq!(literal, "?!");
+}
+
+fn like(txn: &TransactionMut<Schema>) {
+    let matches = |value: &str, pattern: &str| {
+        let value = value.into_column();
+        txn.query_one((value.like_escape(pattern, '!'), value.not_like(pattern)))
+    };
+    assert_eq!(matches("100%", "100!%"), (true, true));
+    assert_eq!(matches("1000", "100!%"), (false, true));
+    assert_eq!(matches("a\\b", "a\\b"), (true, true));
+    assert_eq!(matches("A_B", "a!_b"), (true, true));
+    assert_eq!(matches("a_b", "a%"), (true, false));
+
+    let search = |value: &str, needle: &str| {
+        let value = value.into_column();
+        txn.query_one((
+            value.istarts_with(needle),
+            (value.iends_with(needle), value.icontains(needle)),
+        ))
+    };
+    assert_eq!(search("Paranoid", "PARA"), (true, (false, true)));
+    assert_eq!(search("Paranoid", "Noid"), (false, (true, true)));
+    assert_eq!(search("50% off", "0%"), (false, (false, true)));
+    assert_eq!(search("500 off", "0%"), (false, (false, false)));
+    assert_eq!(search("a_b\\c", "_B\\"), (false, (false, true)));
+    assert_eq!(search("Ärger", "ä"), (false, (false, false)));
+}
+
+fn concat(txn: &TransactionMut<Schema>) {
+    let names = txn.query(|rows| {
+        let track = Track::join(rows);
+        let full = track.name().concat(" - ").concat(track.genre().name());
+        rows.filter(full.eq("Highway - Rock").not());
+        let album = optional(|row| {
+            let album = row.and(track.album());
+            row.then(album.title())
+        });
+        let credits = concat_ws(", ", [Some(full).into_column(), track.composer(), album]);
+        rows.into_vec(credits)
+    });
+    assert_eq!(
+        names,
+        [
+            "Paranoid - Metal, Paranoid",
+            "So What - Jazz, Miles Davis, Kind of Blue"
+        ]
+    );
+}
@@ -0,0 +1,56 @@
+use rust_query::{
+    migration::{schema, Config},
+    raw_sql, IntoColumn, LocalClient, Table,
+};
+
+#[schema]
+enum Schema {
+    Reading {
+        sensor: String,
+        value: f64,
+        bias: Option<f64>,
+    },
+}
+use v0::*;
+
+#[test]
+fn f32_column() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    let a = txn.insert(Reading {
+        sensor: "a",
+        value: 1.5,
+        bias: Some(0.25),
+    });
+    txn.insert(Reading {
+        sensor: "b",
+        value: 2.25,
+        bias: None::<f64>,
+    });
+    assert_eq!(txn.query_one(a.value().into_trivial::<f32>()), 1.5f32);
+    assert_eq!(
+        txn.query_one(a.bias().into_trivial::<Option<f32>>()),
+        Some(0.25f32)
+    );
+
+    let corrected = txn.query(|rows| {
+        let reading = Reading::join(rows);
+        rows.filter(reading.value().lt(2.));
+        let bias = reading.bias().unwrap_or(0.);
+        rows.into_vec(reading.value().add(bias).into_trivial::<f32>())
+    });
+    assert_eq!(corrected, [1.75f32]);
+
+    // values that do not fit in an f32 can not be read
+    let too_large = raw_sql::<f64, _>("1e300", ()).into_trivial::<f32>();
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        txn.query_one(too_large);
+    }));
+    assert!(res.is_err());
+}
@@ -0,0 +1,47 @@
+use rust_query::{
+    migration::{schema, Config},
+    Dummy, LocalClient, TableRow,
+};
+
+#[schema]
+enum Schema {
+    Product { name: String, price: i64 },
+}
+use v0::*;
+
+#[derive(Dummy, Clone, Debug, PartialEq)]
+#[rust_query(From = Product)]
+struct ProductInfo {
+    name: String,
+    price: i64,
+}
+
+#[test]
+fn fetch_many() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    let products: Vec<_> = (0..10)
+        .map(|price| {
+            txn.insert(Product {
+                name: format!("product {price}"),
+                price,
+            })
+        })
+        .collect();
+
+    // an order that is not the insertion order
+    let ids: Vec<TableRow<Product>> = [7, 2, 9, 0, 2].map(|i| products[i]).to_vec();
+    let res: Vec<ProductInfo> = txn.fetch_many(&ids);
+    let prices: Vec<_> = res.iter().map(|x| x.price).collect();
+    assert_eq!(prices, [7, 2, 9, 0, 2]);
+    assert_eq!(res[0].name, "product 7");
+
+    let res: Vec<ProductInfo> = txn.fetch_many(&[]);
+    assert_eq!(res, []);
+}
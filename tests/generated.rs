@@ -0,0 +1,51 @@
+use rust_query::{
+    migration::{schema, Config},
+    LocalClient, Table,
+};
+
+#[schema]
+enum Schema {
+    Customer {
+        name: String,
+        #[generated("lower(name)", stored)]
+        search_name: String,
+        #[generated("length(name)")]
+        name_len: i64,
+    },
+}
+use v0::*;
+
+#[test]
+fn generated() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    let alice = txn.insert(Customer { name: "Alice" });
+    assert_eq!(
+        txn.query_one((alice.search_name(), alice.name_len())),
+        ("alice".to_owned(), 5)
+    );
+
+    txn.update(alice, Customer { name: "ALICE BOB" });
+    assert_eq!(txn.query_one(alice.search_name()), "alice bob");
+
+    let found = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        rows.filter(customer.search_name().eq("alice bob"));
+        rows.into_vec(customer.name_len())
+    });
+    assert_eq!(found, [9]);
+
+    // the returned values are read after the generated columns are updated
+    let updated = txn.update_returning(
+        alice,
+        Customer { name: "Carol" },
+        (alice.search_name(), alice.name_len()),
+    );
+    assert_eq!(updated, ("carol".to_owned(), 5));
+}
@@ -0,0 +1,81 @@
+use rust_query::{
+    aggregate,
+    migration::{schema, Config},
+    LocalClient, Table, TransactionMut,
+};
+
+#[schema]
+enum Schema {
+    Player {
+        #[unique]
+        pub_id: i64,
+        name: String,
+        #[default(0)]
+        score: i64,
+    },
+}
+use v0::*;
+
+fn players(txn: &TransactionMut<Schema>) -> Vec<i64> {
+    txn.query(|rows| {
+        let player = Player::join(rows);
+        rows.into_vec(player.pub_id())
+    })
+}
+
+#[test]
+fn insert_batch() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    // more rows than fit in a single statement
+    let rows = txn
+        .try_insert_batch((0..2000).map(|pub_id| Player {
+            pub_id,
+            name: "player",
+            score: (),
+        }))
+        .unwrap();
+    assert_eq!(rows.len(), 2000);
+    assert_eq!(txn.query_one(rows[1234].pub_id()), 1234);
+    let total = txn.query_one(aggregate(|rows| {
+        let player = Player::join(rows);
+        (rows.count(), rows.sum(player.score()))
+    }));
+    assert_eq!(total, (2000, 0));
+
+    let rows = txn
+        .try_insert_batch([(2000, 5), (2001, 7)].map(|(pub_id, score)| Player {
+            pub_id,
+            name: "scored",
+            score,
+        }))
+        .unwrap();
+    let scores: Vec<_> = rows.iter().map(|row| txn.query_one(row.score())).collect();
+    assert_eq!(scores, [5, 7]);
+
+    // the third value conflicts with an existing row
+    let res = txn.try_insert_batch([3000, 3001, 10, 3002].map(|pub_id| Player {
+        pub_id,
+        name: "conflict",
+        score: (),
+    }));
+    let conflict = res.unwrap_err();
+    assert_eq!(txn.query_one(conflict.pub_id()), 10);
+    assert_eq!(players(&txn)[2002..], [3000, 3001]);
+
+    // values in the batch can also conflict with each other
+    let res = txn.try_insert_batch([4000, 4001, 4000].map(|pub_id| Player {
+        pub_id,
+        name: "duplicate",
+        score: (),
+    }));
+    let conflict = res.unwrap_err();
+    assert_eq!(txn.query_one(conflict.pub_id()), 4000);
+    assert_eq!(players(&txn)[2004..], [4000, 4001]);
+}
@@ -0,0 +1,59 @@
+use rust_query::{
+    migration::{schema, Config},
+    LocalClient, Table,
+};
+
+#[schema]
+enum Schema {
+    Word {
+        #[unique]
+        text: String,
+        count: i64,
+        first_seen: i64,
+    },
+}
+use v0::*;
+
+#[test]
+fn insert_or_update() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    let mut rows = vec![];
+    for (line, text) in ["the", "cat", "the", "dog", "the"].into_iter().enumerate() {
+        let row = txn.insert_or_update(
+            Word {
+                text,
+                count: 1,
+                first_seen: line as i64,
+            },
+            |word| Word {
+                count: word.count().add(1),
+                ..Word::dummy(word)
+            },
+        );
+        rows.push(row);
+    }
+    // the existing row is returned when it is updated
+    assert_eq!(rows[0], rows[2]);
+    assert_eq!(rows[0], rows[4]);
+    assert_ne!(rows[0], rows[1]);
+
+    let words = txn.query(|rows| {
+        let word = Word::join(rows);
+        rows.into_vec((word.text(), (word.count(), word.first_seen())))
+    });
+    assert_eq!(
+        words,
+        [
+            ("cat".to_owned(), (1, 1)),
+            ("dog".to_owned(), (1, 3)),
+            ("the".to_owned(), (3, 0)),
+        ]
+    );
+}
@@ -0,0 +1,88 @@
+use rust_query::{
+    aggregate,
+    migration::{schema, Config},
+    IntoColumn, LocalClient, Table,
+};
+
+#[schema]
+enum Schema {
+    Customer {
+        name: String,
+    },
+    Invoice {
+        customer: Customer,
+        total: f64,
+        paid: bool,
+    },
+    Archive {
+        customer: Customer,
+        total: f64,
+        reason: String,
+    },
+}
+use v0::*;
+
+#[test]
+fn insert_select() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    let alice = txn.insert(Customer { name: "Alice" });
+    let bob = txn.insert(Customer { name: "Bob" });
+    for (customer, total, paid) in [(alice, 10., true), (alice, 5., false), (bob, 7.5, true)] {
+        txn.insert(Invoice {
+            customer,
+            total,
+            paid,
+        });
+    }
+
+    let archived = txn.insert_select::<Archive>(|rows| {
+        let invoice = Invoice::join(rows);
+        rows.filter(invoice.paid());
+        Archive {
+            customer: invoice.customer(),
+            total: invoice.total(),
+            reason: "paid".into_column(),
+        }
+    });
+    assert_eq!(archived, 2);
+
+    let archive = txn.query(|rows| {
+        let archive = Archive::join(rows);
+        rows.into_vec((
+            archive.customer().name(),
+            (archive.total(), archive.reason()),
+        ))
+    });
+    assert_eq!(
+        archive,
+        [
+            ("Alice".to_owned(), (10., "paid".to_owned())),
+            ("Bob".to_owned(), (7.5, "paid".to_owned()))
+        ]
+    );
+
+    // nothing is inserted when the query has no rows
+    let archived = txn.insert_select::<Archive>(|rows| {
+        let invoice = Invoice::join(rows);
+        rows.filter(invoice.total().lt(0.));
+        Archive {
+            customer: invoice.customer(),
+            total: invoice.total(),
+            reason: "negative".into_column(),
+        }
+    });
+    assert_eq!(archived, 0);
+
+    let count = txn.query_one(aggregate(|rows| {
+        Archive::join(rows);
+        rows.count()
+    }));
+    assert_eq!(count, 2);
+}
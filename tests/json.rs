@@ -0,0 +1,83 @@
+#![cfg(feature = "json")]
+
+use std::collections::BTreeMap;
+
+use rust_query::{
+    migration::{schema, Config},
+    Json, LocalClient, Table,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub theme: String,
+    pub volume: Option<i64>,
+    pub shortcuts: BTreeMap<String, String>,
+}
+
+#[schema]
+enum Schema {
+    User {
+        name: String,
+        settings: rust_query::Json<crate::Settings>,
+        tags: Option<rust_query::Json<Vec<String>>>,
+    },
+}
+use v0::*;
+
+#[test]
+fn json() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    let settings = Settings {
+        theme: "dark".to_owned(),
+        volume: None,
+        shortcuts: BTreeMap::from([("save".to_owned(), "ctrl+s".to_owned())]),
+    };
+    let alice = txn.insert(User {
+        name: "Alice",
+        settings: Json(settings.clone()),
+        tags: Some(Json(vec!["admin".to_owned()])),
+    });
+    let bob = txn.insert(User {
+        name: "Bob",
+        settings: &Json(settings.clone()),
+        tags: None::<Json<Vec<String>>>,
+    });
+
+    assert_eq!(txn.query_one(alice.settings()), Json(settings.clone()));
+    assert_eq!(
+        txn.query_one(alice.tags()),
+        Some(Json(vec!["admin".to_owned()]))
+    );
+    assert_eq!(txn.query_one(bob.tags()), None);
+
+    // values inside the json can be used in queries
+    let dark = txn.query(|rows| {
+        let user = User::join(rows);
+        let theme = rows.filter_some(user.settings().get::<String>("$.theme"));
+        rows.filter(theme.eq("dark"));
+        rows.into_vec(user.name())
+    });
+    assert_eq!(dark, ["Alice", "Bob"]);
+
+    // `null` and missing values are both `None`
+    let volume = txn.query_one(alice.settings().get::<i64>("$.volume"));
+    assert_eq!(volume, None);
+    let missing = txn.query_one(alice.settings().get::<i64>("$.missing"));
+    assert_eq!(missing, None);
+
+    // objects are returned as json text
+    let shortcuts = txn.query_one(
+        alice
+            .settings()
+            .get::<Json<BTreeMap<String, String>>>("$.shortcuts"),
+    );
+    assert_eq!(shortcuts, Some(Json(settings.shortcuts)));
+}
@@ -0,0 +1,54 @@
+use rust_query::{
+    migration::{schema, Alter, Config},
+    IntoDummy, LocalClient, Table,
+};
+
+#[schema]
+#[version(0..=1)]
+enum Schema {
+    Measurement {
+        value: f64,
+        #[version(1..)]
+        rounded: i64,
+    },
+}
+use v1::*;
+
+#[test]
+fn migration_progress() {
+    let mut calls = vec![];
+
+    let mut client = LocalClient::try_new().unwrap();
+    let config = Config::open_in_memory().init_stmt(
+        "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 25)
+        INSERT INTO measurement (value) SELECT i * 1.5 FROM n",
+    );
+    let m = client
+        .migrator(config)
+        .unwrap()
+        .progress(10, |table, rows| calls.push((table.to_owned(), rows)));
+    let m = m.migrate(update::Schema {
+        measurement: Box::new(|measurement| {
+            Alter::new(update::MeasurementMigration {
+                rounded: measurement.value().map_dummy(|x| x.round() as i64),
+            })
+        }),
+    });
+    let db = m.finish().unwrap();
+
+    assert_eq!(
+        calls,
+        [
+            ("measurement".to_owned(), 10),
+            ("measurement".to_owned(), 20),
+            ("measurement".to_owned(), 25)
+        ]
+    );
+
+    let txn = client.transaction(&db);
+    let total = txn.query_one(rust_query::aggregate(|rows| {
+        let measurement = Measurement::join(rows);
+        rows.sum(measurement.rounded())
+    }));
+    assert_eq!(total, 494);
+}
@@ -0,0 +1,79 @@
+use rust_query::{
+    migration::{schema, Config},
+    LocalClient, Table,
+};
+
+#[schema]
+enum Schema {
+    Artist { name: String, country: String },
+}
+use v0::*;
+
+#[test]
+fn order_by() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    for (name, country) in [
+        ("abba", "SE"),
+        ("Beatles", "UK"),
+        ("ACDC", "AU"),
+        ("blur", "UK"),
+        ("Adele", "UK"),
+    ] {
+        txn.insert(Artist { name, country });
+    }
+
+    // the first key is the most important
+    let artists = txn.query(|rows| {
+        let artist = Artist::join(rows);
+        rows.order_by_desc(artist.country());
+        rows.order_by_asc_collate(artist.name(), "NOCASE");
+        rows.into_vec(artist.name())
+    });
+    assert_eq!(artists, ["Adele", "Beatles", "blur", "abba", "ACDC"]);
+
+    // the default `BINARY` collation puts upper case letters first
+    let artists = txn.query(|rows| {
+        let artist = Artist::join(rows);
+        rows.order_by_desc_collate(artist.name(), "BINARY");
+        rows.limit(2);
+        rows.into_vec(artist.name())
+    });
+    assert_eq!(artists, ["blur", "abba"]);
+
+    // limits and offsets that do not fit in an i64 are fine
+    let artists = txn.query(|rows| {
+        let artist = Artist::join(rows);
+        rows.limit(u64::MAX);
+        rows.into_vec(artist.name())
+    });
+    assert_eq!(artists.len(), 5);
+    let artists = txn.query(|rows| {
+        let artist = Artist::join(rows);
+        rows.offset(u64::MAX);
+        rows.into_vec(artist.name())
+    });
+    assert_eq!(artists, Vec::<String>::new());
+
+    // ties are sorted by the returned columns
+    let countries = txn.query(|rows| {
+        let artist = Artist::join(rows);
+        rows.order_by_asc(artist.country().eq("UK"));
+        rows.into_vec(artist.country())
+    });
+    assert_eq!(countries, ["AU", "SE", "UK", "UK", "UK"]);
+
+    // getting the sql does not add columns to the query
+    let artists = txn.query(|rows| {
+        let artist = Artist::join(rows);
+        rows.to_sql(artist.country());
+        rows.into_vec(artist.name())
+    });
+    assert_eq!(artists, ["ACDC", "Adele", "Beatles", "abba", "blur"]);
+}
@@ -0,0 +1,42 @@
+use rust_query::{
+    call_fn,
+    migration::{schema, Config},
+    raw_sql, LocalClient,
+};
+
+#[schema]
+enum Schema {
+    Note { text: String },
+}
+use v0::*;
+
+#[test]
+fn readonly() {
+    let mut client = LocalClient::try_new().unwrap();
+    // this function writes using the connection of the query that calls it
+    let config = Config::open_in_memory().scalar_function("try_write", 0, |ctx| {
+        let conn = unsafe { ctx.get_connection() }?;
+        Ok(conn
+            .execute("INSERT INTO note (text) VALUES ('sneaky')", ())
+            .is_ok())
+    });
+    let database = client.migrator(config).unwrap().finish().unwrap();
+    let query_only = || raw_sql::<bool, _>("SELECT query_only FROM pragma_query_only", ());
+    let try_write = || call_fn::<bool, _>("try_write", ());
+
+    let txn = client.transaction_readonly(&database);
+    assert!(txn.query_one(query_only()));
+    assert!(!txn.query_one(try_write()));
+    drop(txn);
+
+    // the pragma is reset when the read-only transaction is dropped
+    let txn = client.transaction(&database);
+    assert!(!txn.query_one(query_only()));
+    assert!(txn.query_one(try_write()));
+    drop(txn);
+
+    let mut txn = client.transaction_mut(&database);
+    assert!(!txn.query_one(query_only()));
+    txn.insert(Note { text: "hello" });
+    txn.commit();
+}
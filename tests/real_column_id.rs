@@ -0,0 +1,46 @@
+use rust_query::{
+    migration::{schema, Config},
+    LocalClient, Table,
+};
+
+#[schema]
+enum Schema {
+    Measurement {
+        unit: String,
+        value: f64,
+        error: Option<f64>,
+    },
+}
+use v0::*;
+
+/// sqlite can give the id as a `REAL` from `INSERT .. RETURNING` when the last columns are `REAL`.
+#[test]
+fn real_columns() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    let first = txn.insert(Measurement {
+        unit: "m",
+        value: 1.5,
+        error: Some(0.25),
+    });
+    let second = txn.insert(Measurement {
+        unit: "cm",
+        value: 20.,
+        error: None::<f64>,
+    });
+    assert_ne!(first, second);
+    assert_eq!(txn.query_one(first.unit()), "m");
+    assert_eq!(txn.query_one(second.value()), 20.);
+
+    let all = txn.query(|rows| {
+        let m = Measurement::join(rows);
+        rows.into_vec(m)
+    });
+    assert_eq!(all, [first, second]);
+}
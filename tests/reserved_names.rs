@@ -0,0 +1,66 @@
+use rust_query::{
+    aggregate,
+    migration::{schema, Config},
+    LocalClient, Table,
+};
+
+#[schema]
+enum Schema {
+    Group {
+        #[unique]
+        select: String,
+    },
+    Order {
+        group: Group,
+        from: i64,
+    },
+}
+use v0::*;
+
+/// Tables and columns can be named after sqlite keywords, because all names are quoted.
+#[test]
+fn reserved_names() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    let group = txn.try_insert(Group { select: "where" }).unwrap();
+    txn.insert(Order { group, from: 1 });
+    txn.insert(Order { group, from: 2 });
+
+    let orders = txn.query(|rows| {
+        let order = Order::join(rows);
+        rows.filter(order.from().lt(2));
+        rows.into_vec((order.group().select(), order.from()))
+    });
+    assert_eq!(orders, [("where".to_owned(), 1)]);
+
+    let total = txn.query_one(aggregate(|rows| {
+        let order = Order::join(rows);
+        rows.filter_on(order.group(), group);
+        rows.sum(order.from())
+    }));
+    assert_eq!(total, 3);
+
+    let updated = txn.update_where::<Order>(|rows, order| {
+        rows.filter(order.group().select().eq("where"));
+        Order {
+            from: order.from().add(10),
+            ..Order::dummy(order)
+        }
+    });
+    assert_eq!(updated, 2);
+    assert_eq!(txn.query_one(Group::unique("where")), Some(group));
+
+    let mut txn = txn.downgrade();
+    let deleted = txn.delete_where(|rows| {
+        let order = Order::join(rows);
+        rows.filter(order.from().lt(12));
+        order
+    });
+    assert_eq!(deleted, Ok(1));
+}
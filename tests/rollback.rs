@@ -0,0 +1,53 @@
+use rust_query::{
+    aggregate,
+    migration::{schema, Config},
+    Database, LocalClient, Table, TransactionMut,
+};
+
+#[schema]
+enum Schema {
+    Payment { amount: i64 },
+}
+use v0::*;
+
+fn total(client: &mut LocalClient, database: &Database<Schema>) -> i64 {
+    let txn = client.transaction(database);
+    txn.query_one(aggregate(|rows| {
+        let payment = Payment::join(rows);
+        rows.sum(payment.amount())
+    }))
+}
+
+/// The decision to commit is made by a different function than the one making the changes.
+fn finish(txn: TransactionMut<Schema>, confirmed: bool) {
+    if confirmed {
+        txn.commit();
+    } else {
+        txn.rollback();
+    }
+}
+
+#[test]
+fn rollback() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+
+    let mut txn = client.transaction_mut(&database);
+    txn.insert(Payment { amount: 10 });
+    finish(txn, true);
+    assert_eq!(total(&mut client, &database), 10);
+
+    let mut txn = client.transaction_mut(&database);
+    txn.insert(Payment { amount: 5 });
+    finish(txn, false);
+    assert_eq!(total(&mut client, &database), 10);
+
+    let mut txn = client.transaction_mut(&database).downgrade();
+    txn.delete_where(|rows| Payment::join(rows)).unwrap();
+    txn.rollback();
+    assert_eq!(total(&mut client, &database), 10);
+}
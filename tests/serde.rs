@@ -0,0 +1,34 @@
+#![cfg(feature = "serde")]
+
+use rust_query::{
+    migration::{schema, Config},
+    LocalClient, UncheckedRow,
+};
+
+#[schema]
+enum Schema {
+    User { name: String },
+}
+use v0::*;
+
+#[test]
+fn serde() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    let alice = txn.insert(User { name: "Alice" });
+    let json = serde_json::to_string(&alice).unwrap();
+
+    let unchecked: UncheckedRow<User> = serde_json::from_str(&json).unwrap();
+    assert_eq!(unchecked, UncheckedRow::from(alice));
+    assert_eq!(txn.check_row(unchecked), Some(alice));
+
+    // there is no user with this id
+    let unchecked: UncheckedRow<User> = serde_json::from_str("1000").unwrap();
+    assert_eq!(txn.check_row(unchecked), None);
+}
@@ -0,0 +1,66 @@
+use std::{fs, sync::mpsc, thread};
+
+use rust_query::{
+    aggregate,
+    migration::{schema, Config},
+    Database, LocalClient, Table, Transaction,
+};
+
+#[schema]
+enum Schema {
+    Event { name: String },
+}
+use v0::*;
+
+fn event_count(txn: &Transaction<Schema>) -> i64 {
+    txn.query_one(aggregate(|rows| {
+        let event = Event::join(rows);
+        rows.count_distinct(event)
+    }))
+}
+
+fn insert_event(client: &mut LocalClient, db: &Database<Schema>, name: &str) {
+    let mut txn = client.transaction_mut(db);
+    txn.insert(Event { name });
+    txn.commit();
+}
+
+/// Writes that are committed while a [Transaction] is alive are not visible in that [Transaction].
+#[test]
+fn snapshot_isolation() {
+    // WAL snapshots require a database file.
+    let dir = std::env::temp_dir().join(format!("rust-query-snapshot-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("snapshot.sqlite");
+
+    let mut client = LocalClient::try_new().unwrap();
+    let db = client
+        .migrator(Config::open(&path))
+        .unwrap()
+        .finish()
+        .unwrap();
+    insert_event(&mut client, &db, "first");
+
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|s| {
+        let txn = client.transaction(&db);
+        assert_eq!(event_count(&txn), 1);
+
+        s.spawn(|| {
+            let mut client = LocalClient::try_new().unwrap();
+            insert_event(&mut client, &db, "second");
+            tx.send(()).unwrap();
+        });
+        rx.recv().unwrap();
+
+        // the write was committed, but it is not part of our snapshot
+        assert_eq!(event_count(&txn), 1);
+    });
+
+    let txn = client.transaction(&db);
+    assert_eq!(event_count(&txn), 2);
+    drop(txn);
+    drop(client);
+
+    fs::remove_dir_all(dir).unwrap();
+}
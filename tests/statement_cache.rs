@@ -0,0 +1,66 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rusqlite::hooks::{AuthAction, Authorization};
+use rust_query::{
+    migration::{schema, Config},
+    LocalClient, Table,
+};
+
+#[schema]
+enum Schema {
+    Item { price: i64 },
+}
+use v0::*;
+
+/// Number of `SELECT` statements that were compiled.
+static PREPARED: AtomicUsize = AtomicUsize::new(0);
+
+#[test]
+fn statement_cache() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+    for price in [10, 20, 30] {
+        txn.insert(Item { price });
+    }
+    txn.commit();
+
+    // the authorizer is called when a statement is compiled, not when it is executed
+    let mut txn = client.transaction_mut(&database).downgrade();
+    txn.unchecked_transaction()
+        .authorizer(Some(|ctx: rusqlite::hooks::AuthContext| {
+            if let AuthAction::Select = ctx.action {
+                PREPARED.fetch_add(1, Ordering::Relaxed);
+            }
+            Authorization::Allow
+        }));
+    drop(txn);
+
+    // the same query with different parameters in different transactions
+    for max in [15, 25, 35] {
+        let txn = client.transaction(&database);
+        let count = txn.query(|rows| {
+            let item = Item::join(rows);
+            rows.filter(item.price().lt(max));
+            rows.into_vec(item.price()).len()
+        });
+        assert_eq!(count as i64, max / 10);
+    }
+    assert_eq!(PREPARED.load(Ordering::Relaxed), 1);
+
+    // row references are bound as parameters, so looking up different rows uses the same statement
+    let txn = client.transaction(&database);
+    let items = txn.query(|rows| {
+        let item = Item::join(rows);
+        rows.into_vec(item)
+    });
+    assert_eq!(txn.query_one(items[0].price()), 10);
+    let prepared = PREPARED.load(Ordering::Relaxed);
+    assert_eq!(txn.query_one(items[1].price()), 20);
+    assert_eq!(txn.query_one(items[2].price()), 30);
+    assert_eq!(PREPARED.load(Ordering::Relaxed), prepared);
+}
@@ -0,0 +1,57 @@
+use std::sync::Mutex;
+
+use rust_query::{
+    migration::{schema, Config},
+    LocalClient, Table,
+};
+
+#[schema]
+enum Schema {
+    Customer {
+        #[unique]
+        email: String,
+        city: String,
+    },
+}
+use v0::*;
+
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+fn take_warnings() -> Vec<String> {
+    std::mem::take(&mut *WARNINGS.lock().unwrap())
+}
+
+#[test]
+fn table_scan() {
+    let config = Config::open_in_memory().warn_on_table_scan(|_sql, detail| {
+        WARNINGS.lock().unwrap().push(detail.to_owned());
+    });
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client.migrator(config).unwrap().finish().unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    txn.try_insert(Customer {
+        email: "alice@example.com",
+        city: "Delft",
+    })
+    .unwrap();
+    assert_eq!(take_warnings(), Vec::<String>::new());
+
+    // there is no index on `city`
+    let emails = txn.query(|rows| {
+        let customer = Customer::join(rows);
+        rows.filter(customer.city().eq("Delft"));
+        rows.into_vec(customer.email())
+    });
+    assert_eq!(emails, ["alice@example.com"]);
+    // the index of the unique constraint is only used for the order of the results
+    assert_eq!(
+        take_warnings(),
+        ["SCAN _0 USING INDEX sqlite_autoindex_customer_1"]
+    );
+
+    // the unique constraint on `email` has an index
+    let found = txn.query_one(Customer::unique("alice@example.com").is_some());
+    assert!(found);
+    assert_eq!(take_warnings(), Vec::<String>::new());
+}
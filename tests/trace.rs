@@ -0,0 +1,92 @@
+use std::sync::Mutex;
+
+use rust_query::{
+    migration::{schema, Config},
+    IntoDummy, LocalClient, Table,
+};
+
+#[schema]
+enum Schema {
+    #[no_reference]
+    Task {
+        title: String,
+        done: bool,
+    },
+    User {
+        name: String,
+    },
+    Comment {
+        user: User,
+        text: String,
+    },
+}
+use v0::*;
+
+static STATEMENTS: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+#[test]
+fn trace() {
+    let mut client = LocalClient::try_new().unwrap();
+    let config = Config::open_in_memory().trace(|sql, _duration| {
+        STATEMENTS.lock().unwrap().push(sql.to_owned());
+    });
+    let database = client.migrator(config).unwrap().finish().unwrap();
+    STATEMENTS.lock().unwrap().clear();
+
+    let mut txn = client.transaction_mut(&database);
+    let task = txn.insert(Task {
+        title: "write tests",
+        done: false,
+    });
+    txn.update(
+        task,
+        Task {
+            title: task.title(),
+            done: true,
+        },
+    );
+    let titles = txn.query(|rows| {
+        let task = Task::join(rows);
+        rows.filter(task.done());
+        rows.into_vec(task.title())
+    });
+    assert_eq!(titles, ["write tests"]);
+    let mut txn = txn.downgrade();
+    assert!(txn.delete(task));
+    txn.commit();
+
+    let statements = STATEMENTS.lock().unwrap();
+    for keyword in ["INSERT", "UPDATE", "SELECT", "DELETE", "COMMIT"] {
+        assert!(
+            statements.iter().any(|sql| sql.starts_with(keyword)),
+            "no {keyword} in {statements:?}"
+        );
+    }
+    drop(statements);
+
+    // the values are returned by the insert or update, without a separate query
+    let mut txn = client.transaction_mut(&database);
+    let alice = txn.insert(User { name: "Alice" });
+    let (comment, name) = txn.insert_returning(
+        Comment {
+            user: alice,
+            text: "hello",
+        },
+        |comment| (comment.clone(), comment.user().name()).into_dummy(),
+    );
+    assert_eq!(name, "Alice");
+    let last = STATEMENTS.lock().unwrap().pop().unwrap();
+    assert!(last.starts_with("INSERT") && last.contains(" RETURNING "));
+    let updated = txn.update_returning(
+        comment,
+        Comment {
+            user: alice,
+            text: comment.text().concat(" world"),
+        },
+        (comment.text(), comment.user().name()),
+    );
+    assert_eq!(updated, ("hello world".to_owned(), "Alice".to_owned()));
+    let statements = STATEMENTS.lock().unwrap();
+    let last = statements.last().unwrap();
+    assert!(last.starts_with("UPDATE") && last.contains(" RETURNING "));
+}
@@ -0,0 +1,135 @@
+use rust_query::{
+    migration::{schema, Config},
+    IntoColumn, LocalClient, Table, TransactionMut,
+};
+
+#[schema]
+enum Schema {
+    District {
+        name: String,
+    },
+    Delivery {
+        district: District,
+        carrier: Option<i64>,
+        attempts: i64,
+    },
+    Player {
+        #[unique]
+        pub_id: i64,
+        score: i64,
+    },
+}
+use v0::*;
+
+#[test]
+fn test_update_where() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    update_where(&mut txn);
+    try_update_where(&mut txn);
+}
+
+fn update_where(txn: &mut TransactionMut<Schema>) {
+    let north = txn.insert(District { name: "North" });
+    let south = txn.insert(District { name: "South" });
+    for district in [north, north, south] {
+        txn.insert(Delivery {
+            district,
+            carrier: None::<i64>,
+            attempts: 0,
+        });
+    }
+
+    let updated = txn.update_where::<Delivery>(|rows, delivery| {
+        rows.filter(delivery.district().name().eq("North"));
+        Delivery {
+            carrier: Some(3).into_column(),
+            attempts: delivery.attempts().add(1),
+            ..Delivery::dummy(delivery)
+        }
+    });
+    assert_eq!(updated, 2);
+
+    let deliveries = txn.query(|rows| {
+        let delivery = Delivery::join(rows);
+        rows.into_vec((
+            delivery.district().name(),
+            (delivery.carrier(), delivery.attempts()),
+        ))
+    });
+    assert_eq!(
+        deliveries,
+        [
+            ("North".to_owned(), (Some(3), 1)),
+            ("North".to_owned(), (Some(3), 1)),
+            ("South".to_owned(), (None, 0)),
+        ]
+    );
+
+    // every delivery of the north district is returned twice, but only updated once
+    let updated = txn.update_where::<Delivery>(|rows, delivery| {
+        let other = Delivery::join(rows);
+        rows.filter(other.district().eq(delivery.district()));
+        rows.filter(delivery.district().name().eq("North"));
+        Delivery {
+            attempts: delivery.attempts().add(1),
+            ..Delivery::dummy(delivery)
+        }
+    });
+    assert_eq!(updated, 2);
+    let attempts = txn.query(|rows| {
+        let delivery = Delivery::join(rows);
+        rows.into_vec((delivery.district().name(), delivery.attempts()))
+    });
+    assert_eq!(
+        attempts,
+        [
+            ("North".to_owned(), 2),
+            ("North".to_owned(), 2),
+            ("South".to_owned(), 0),
+        ]
+    );
+
+    // nothing matches
+    let updated = txn.update_where::<Delivery>(|rows, delivery| {
+        rows.filter(delivery.attempts().lt(0));
+        Delivery::dummy(delivery)
+    });
+    assert_eq!(updated, 0);
+}
+
+fn try_update_where(txn: &mut TransactionMut<Schema>) {
+    for pub_id in [1, 2, 3] {
+        txn.try_insert(Player { pub_id, score: 0 }).unwrap();
+    }
+
+    // all `pub_id` values are shifted, the result is unique again
+    let res = txn.try_update_where::<Player>(|_rows, player| Player {
+        pub_id: player.pub_id().add(10),
+        ..Player::dummy(player)
+    });
+    assert_eq!(res, Ok(3));
+
+    // two players would get the same `pub_id`, so nothing is updated
+    let res = txn.try_update_where::<Player>(|rows, player| {
+        rows.filter(player.pub_id().lt(13));
+        Player {
+            pub_id: 13.into_column(),
+            score: 100.into_column(),
+        }
+    });
+    let err = res.unwrap_err().to_string();
+    assert!(err.contains("UNIQUE constraint failed"), "{err}");
+
+    let players = txn.query(|rows| {
+        let player = Player::join(rows);
+        rows.into_vec((player.pub_id(), player.score()))
+    });
+    assert_eq!(players, [(11, 0), (12, 0), (13, 0)]);
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "uuid")]
+
+use rust_query::{
+    migration::{schema, Config},
+    LocalClient, Table,
+};
+use uuid::Uuid;
+
+#[schema]
+enum Schema {
+    #[unique(public_id)]
+    Document {
+        public_id: uuid::Uuid,
+        title: String,
+        parent: Option<uuid::Uuid>,
+    },
+}
+use v0::*;
+
+#[test]
+fn uuid() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    let root_id = Uuid::from_u128(0x6f1c_2a8e_9b4d_4e3a_8c2f_1d5e_7a9b_0c3d);
+    let child_id = Uuid::from_u128(0x0a2b_3c4d_5e6f_4a7b_8c9d_0e1f_2a3b_4c5d);
+    let root = txn
+        .try_insert(Document {
+            public_id: root_id,
+            title: "root",
+            parent: None::<Uuid>,
+        })
+        .unwrap();
+    txn.try_insert(Document {
+        public_id: child_id,
+        title: "child",
+        parent: Some(root_id),
+    })
+    .unwrap();
+
+    assert_eq!(txn.query_one(root.public_id()), root_id);
+
+    let found = txn.query_one(Document::unique(child_id));
+    assert_eq!(
+        found.map(|x| txn.query_one(x.title())),
+        Some("child".to_owned())
+    );
+
+    let children = txn.query(|rows| {
+        let doc = Document::join(rows);
+        let parent = rows.filter_some(doc.parent());
+        rows.filter(parent.eq(root_id));
+        rows.into_vec((doc.public_id(), parent))
+    });
+    assert_eq!(children, [(child_id, root_id)]);
+
+    let other_id = Uuid::from_u128(0x1b2c_3d4e_5f6a_4b7c_8d9e_0f1a_2b3c_4d5e);
+    txn.try_update(
+        root,
+        Document {
+            public_id: root.public_id(),
+            title: "renamed",
+            parent: Some(other_id),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        txn.query_one((root.public_id(), root.parent())),
+        (root_id, Some(other_id))
+    );
+
+    // the uuid is stored as 16 bytes
+    let len = txn.query_one(rust_query::call_fn::<i64, _>("length", (root.public_id(),)));
+    assert_eq!(len, 16);
+}
@@ -0,0 +1,175 @@
+use rust_query::{
+    aggregate,
+    migration::{schema, Config},
+    window, LocalClient, Table,
+};
+
+#[schema]
+enum Schema {
+    Genre {
+        name: String,
+    },
+    Track {
+        name: String,
+        genre: Genre,
+        milliseconds: i64,
+    },
+}
+use v0::*;
+
+#[test]
+fn window_functions() {
+    let mut client = LocalClient::try_new().unwrap();
+    let database = client
+        .migrator(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&database);
+
+    let rock = txn.insert(Genre { name: "Rock" });
+    let jazz = txn.insert(Genre { name: "Jazz" });
+    for (name, genre, milliseconds) in [
+        ("a", rock, 300),
+        ("b", rock, 200),
+        ("c", rock, 300),
+        ("d", rock, 100),
+        ("e", jazz, 500),
+    ] {
+        txn.insert(Track {
+            name,
+            genre,
+            milliseconds,
+        });
+    }
+
+    let ranks = txn.query(|rows| {
+        let track = Track::join(rows);
+        let (rank, dense_rank) = window(|w| {
+            w.partition_by(track.genre());
+            w.order_by_desc(track.milliseconds());
+            (w.rank(), w.dense_rank())
+        });
+        let row_number = window(|w| {
+            w.partition_by(track.genre());
+            w.order_by_desc(track.milliseconds());
+            w.order_by_asc(track.name());
+            w.row_number()
+        });
+        rows.order_by_asc(track.name());
+        rows.into_vec((track.name(), (row_number, (rank, dense_rank))))
+    });
+    let expected = [
+        ("a", 1, 1, 1),
+        ("b", 3, 3, 2),
+        ("c", 2, 1, 1),
+        ("d", 4, 4, 3),
+        ("e", 1, 1, 1),
+    ];
+    let expected: Vec<_> = expected
+        .into_iter()
+        .map(|(name, a, b, c)| (name.to_owned(), (a, (b, c))))
+        .collect();
+    assert_eq!(ranks, expected);
+
+    // without a partition all rows are numbered together
+    let longest = txn.query(|rows| {
+        let track = Track::join(rows);
+        let rank = window(|w| {
+            w.order_by_desc(track.milliseconds());
+            w.rank()
+        });
+        rows.order_by_asc(rank.clone());
+        rows.order_by_asc(track.name());
+        rows.into_vec((track.genre().name(), rank))
+    });
+    let expected = [
+        ("Jazz", 1),
+        ("Rock", 2),
+        ("Rock", 2),
+        ("Rock", 4),
+        ("Rock", 5),
+    ];
+    let expected: Vec<_> = expected
+        .into_iter()
+        .map(|(name, rank)| (name.to_owned(), rank))
+        .collect();
+    assert_eq!(longest, expected);
+
+    // neighbouring rows are None at the partition boundaries
+    let neighbours = txn.query(|rows| {
+        let track = Track::join(rows);
+        let (lag, lead) = window(|w| {
+            w.partition_by(track.genre());
+            w.order_by_asc(track.name());
+            (w.lag(track.milliseconds(), 1), w.lead(track.name(), 2))
+        });
+        rows.order_by_asc(track.name());
+        rows.into_vec((track.name(), (lag, lead)))
+    });
+    let expected = [
+        ("a", None, Some("c")),
+        ("b", Some(300), Some("d")),
+        ("c", Some(200), None),
+        ("d", Some(300), None),
+        ("e", None, None),
+    ];
+    let expected: Vec<_> = expected
+        .into_iter()
+        .map(|(name, lag, lead)| (name.to_owned(), (lag, lead.map(str::to_owned))))
+        .collect();
+    assert_eq!(neighbours, expected);
+
+    // first_value and last_value match joining the same table again in an aggregate
+    let with_window = txn.query(|rows| {
+        let track = Track::join(rows);
+        let (first, last) = window(|w| {
+            w.partition_by(track.genre());
+            w.order_by_asc(track.name());
+            (
+                w.first_value(track.milliseconds()),
+                w.last_value(track.milliseconds()),
+            )
+        });
+        rows.order_by_asc(track.name());
+        rows.into_vec((track.name(), (first, last)))
+    });
+    let with_join = txn.query(|rows| {
+        let track = Track::join(rows);
+        let (first, last) = aggregate(|rows| {
+            let other = Track::join(rows);
+            rows.filter_on(other.genre(), track.genre());
+            (
+                rows.first(other.milliseconds(), other.name()),
+                rows.last(other.milliseconds(), other.name()),
+            )
+        });
+        rows.order_by_asc(track.name());
+        rows.into_vec((track.name(), (first.unwrap_or(-1), last.unwrap_or(-1))))
+    });
+    assert_eq!(with_window, with_join);
+    assert_eq!(with_window[1], ("b".to_owned(), (300, 100)));
+
+    // window functions can be filtered on outside of the sub-query that computes them
+    let longest = txn.query(|rows| {
+        let (track, position) = rows.join_subquery(|rows| {
+            let track = Track::join(rows);
+            let position = window(|w| {
+                w.partition_by(track.genre());
+                w.order_by_desc(track.milliseconds());
+                w.order_by_asc(track.name());
+                w.row_number()
+            });
+            (track, position)
+        });
+        rows.filter(position.eq(1));
+        rows.order_by_asc(track.genre().name());
+        rows.into_vec((track.genre().name(), track.name()))
+    });
+    let expected = [("Jazz", "e"), ("Rock", "a")];
+    let expected: Vec<_> = expected
+        .into_iter()
+        .map(|(genre, name)| (genre.to_owned(), name.to_owned()))
+        .collect();
+    assert_eq!(longest, expected);
+}